@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+
+use crate::texture_atlas::SpriteRef;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AnimationMode {
+    #[default]
+    Loop,
+    // Bleibt nach dem letzten Frame stehen, danach ist is_finished() true
+    Once,
+    // Vorwärts und wieder rückwärts, ohne den Rand doppelt zu zeigen
+    PingPong,
+}
+
+// Eine fertige Animation aus dem Manifest, mit Frames schon als SpriteRefs
+#[derive(Clone, Debug)]
+pub struct AnimationClip {
+    pub frames: Vec<SpriteRef>,
+    pub frame_duration: f32,
+    pub mode: AnimationMode,
+    // (Frame, Event): das Event feuert, sobald der Frame angezeigt wird, z.B. (2, "hit") beim Zuschlagen
+    pub events: Vec<(usize, String)>,
+    pub scale: f32,
+}
+
+#[derive(Clone)]
+pub struct Animation {
+    clip: AnimationClip,
+    pub index: usize,
+    timer: f32,
+    forward: bool,
+    finished: bool,
+    // Die Events vom ersten Frame feuern beim ersten update() nach new()/restart()
+    started: bool,
+}
+
+impl Animation {
+    pub fn new(clip: &AnimationClip) -> Animation {
+        Animation {
+            clip: clip.clone(),
+            index: 0,
+            timer: 0.0,
+            forward: true,
+            finished: false,
+            started: false,
+        }
+    }
+
+    pub fn restart(&mut self) {
+        self.index = 0;
+        self.timer = 0.0;
+        self.forward = true;
+        self.finished = false;
+        self.started = false;
+    }
+
+    pub fn current(&self) -> SpriteRef {
+        self.clip.frames[self.index]
+    }
+
+    // Größe des aktuellen Frames, mit dem scale aus dem Manifest
+    pub fn size(&self) -> (f32, f32) {
+        let frame = self.current();
+        (frame.width() * self.clip.scale, frame.height() * self.clip.scale)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    // Läuft nach Zeit, nicht nach Aufrufen. Gibt alle Events zurück, die dabei gefeuert haben.
+    pub fn update(&mut self, delta_time: f32) -> Vec<String> {
+        let mut fired = Vec::new();
+        if self.finished {
+            return fired;
+        }
+        if !self.started {
+            self.started = true;
+            fired.extend(self.events_at(self.index));
+        }
+
+        let frame_duration = self.clip.frame_duration.max(0.001);
+        self.timer += delta_time;
+        while self.timer >= frame_duration && !self.finished {
+            self.timer -= frame_duration;
+            if self.step() {
+                fired.extend(self.events_at(self.index));
+            }
+        }
+        fired
+    }
+
+    fn events_at(&self, frame: usize) -> impl Iterator<Item = String> + '_ {
+        self.clip
+            .events
+            .iter()
+            .filter(move |(event_frame, _)| *event_frame == frame)
+            .map(|(_, event)| event.clone())
+    }
+
+    // Gibt true zurück, wenn ein neuer Frame dran ist
+    fn step(&mut self) -> bool {
+        let last = self.clip.frames.len() - 1;
+        match self.clip.mode {
+            AnimationMode::Loop => {
+                self.index = if self.index >= last { 0 } else { self.index + 1 };
+            }
+            AnimationMode::Once => {
+                if self.index >= last {
+                    self.finished = true;
+                    return false;
+                }
+                self.index += 1;
+            }
+            AnimationMode::PingPong => {
+                if last == 0 {
+                    return false;
+                }
+                if self.index >= last {
+                    self.forward = false;
+                } else if self.index == 0 {
+                    self.forward = true;
+                }
+                if self.forward {
+                    self.index += 1;
+                } else {
+                    self.index -= 1;
+                }
+            }
+        }
+        true
+    }
+}
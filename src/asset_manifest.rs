@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::{fs::File, io::Read, path::Path};
+
+use crate::animation::AnimationMode;
+
+// Alles, was beim Start geladen wird. Neue Sprites/Animationen/Sounds nur in assets/manifest.ron eintragen.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssetManifest {
+    // Einzelne Texturen, die nicht schon über eine Animation geladen werden
+    #[serde(default)]
+    pub textures: Vec<String>,
+    #[serde(default)]
+    pub animations: HashMap<String, AnimationConfig>,
+    // HP-Stufen des Spielers, von voll nach fast tot
+    #[serde(default)]
+    pub player_tiers: Vec<HpTierConfig>,
+    // Name -> Pfad, abgespielt wird über den Namen
+    #[serde(default)]
+    pub sounds: HashMap<String, String>,
+    // Gruppen aus mehreren Sounds, gespielt wird jedes Mal ein zufälliger davon
+    #[serde(default)]
+    pub sound_groups: HashMap<String, SoundGroupConfig>,
+    // Name -> Pfad, wird gestreamt statt komplett geladen
+    #[serde(default)]
+    pub music: HashMap<String, String>,
+    #[serde(default)]
+    pub footsteps: FootstepConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnimationConfig {
+    pub frames: Vec<String>,
+    // Sekunden pro Frame
+    #[serde(default = "default_frame_duration")]
+    pub frame_duration: f32,
+    #[serde(default)]
+    pub mode: AnimationMode,
+    // (Frame, Event), z.B. (2, "hit")
+    #[serde(default)]
+    pub events: Vec<(usize, String)>,
+    // So viel größer werden die Frames gezeichnet, z.B. damit kleinere Sprites zu den anderen passen
+    #[serde(default = "default_one")]
+    pub scale: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HpTierConfig {
+    // Die Stufe gilt, solange HP > above * max HP
+    pub above: f32,
+    // Namen der Animationen
+    pub idle: String,
+    pub run: String,
+    // Faktor auf die normale Laufgeschwindigkeit
+    #[serde(default = "default_one")]
+    pub speed: f32,
+}
+
+// Schritte je nach Boden. Schlüssel sind die Zeichen aus ground/objects in den .KB-Dateien.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FootstepConfig {
+    #[serde(default)]
+    pub tiles: HashMap<char, Footstep>,
+    // Direkt am Wasser, außer ein Objekt auf dem Tile hat eigene Schritte
+    #[serde(default)]
+    pub shore: Option<Footstep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Footstep {
+    // Name aus sound_groups (oder sounds)
+    pub sound: String,
+    // Kleine Staubwolke bei jedem Schritt
+    #[serde(default)]
+    pub dust: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoundGroupConfig {
+    // Namen aus sounds
+    pub sounds: Vec<String>,
+    // Grundwerte, z.B. um aus einem Sound einen dumpferen zu machen
+    #[serde(default = "default_one")]
+    pub pitch: f32,
+    #[serde(default = "default_one")]
+    pub volume: f32,
+    // z.B. 0.1: Tonhöhe zufällig zwischen 0.9 und 1.1
+    #[serde(default)]
+    pub pitch_jitter: f32,
+    // z.B. 0.2: Lautstärke zufällig zwischen 80% und 100%
+    #[serde(default)]
+    pub volume_jitter: f32,
+}
+
+fn default_one() -> f32 {
+    1.0
+}
+
+fn default_frame_duration() -> f32 {
+    0.12
+}
+
+impl AssetManifest {
+    pub fn new(path: &str) -> Result<AssetManifest, String> {
+        let path = Path::new(path);
+        let display = path.display();
+
+        let mut file = match File::open(&path) {
+            Err(why) => return Err(format!("couldn't open {}: {}", display, why)),
+            Ok(file) => file,
+        };
+
+        let mut s = String::new();
+        match file.read_to_string(&mut s) {
+            Err(why) => return Err(format!("couldn't read {}: {}", display, why)),
+            Ok(_) => (),
+        }
+
+        ron::from_str(s.as_str()).map_err(|why| format!("{}: {}", display, why))
+    }
+
+    // Alle Texturpfade, auch die aus den Animationen, ohne Duplikate
+    pub fn texture_paths(&self) -> Vec<&str> {
+        let mut paths: Vec<&str> = Vec::new();
+        let frames = self.animations.values().flat_map(|animation| animation.frames.iter());
+        for path in self.textures.iter().chain(frames) {
+            if !paths.contains(&path.as_str()) {
+                paths.push(path);
+            }
+        }
+        paths
+    }
+}
+
+// Listet alle fehlenden Assets auf. Im strikten Modus (--strict-assets) ist das ein Fehler,
+// sonst läuft das Spiel mit Schachbrett-Texturen und ohne die Sounds weiter.
+pub fn report_missing(missing: &[String], strict: bool) -> Result<(), String> {
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    let list = missing.iter().map(|path| format!("  {}", path)).collect::<Vec<_>>().join("\n");
+    if strict {
+        return Err(format!("{} asset(s) missing:\n{}", missing.len(), list));
+    }
+    println!("MISSING ASSETS ({}):\n{}", missing.len(), list);
+    Ok(())
+}
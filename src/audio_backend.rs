@@ -0,0 +1,196 @@
+use raylib::core::audio::{Music, RaylibAudio, Sound};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::audiomanager::Bus;
+
+// Ein Sound, so wie er am Ende rausgeht: Gruppe schon ausgewürfelt, Lautstärke schon gemischt
+#[derive(Clone, Debug, PartialEq)]
+pub struct SoundCall {
+    // Was angefragt wurde (Sound oder Gruppe)
+    pub name: String,
+    // Welcher Sound dabei rausgekommen ist
+    pub sound: String,
+    pub bus: Bus,
+    pub volume: f32,
+    pub pitch: f32,
+    pub pan: f32,
+}
+
+// Das, was wirklich Töne macht. AudioManager kümmert sich um Gruppen, Busse und Überblenden,
+// das Backend nur ums Laden und Abspielen. Musik und Sounds werden über ihren Namen angesprochen.
+pub trait AudioBackend {
+    fn load_sound(&mut self, name: &str, path: &str) -> Result<(), String>;
+    // false, wenn es den Sound nicht gibt
+    fn play_sound(&mut self, call: &SoundCall) -> bool;
+
+    fn load_music(&mut self, name: &str, path: &str) -> Result<(), String>;
+    fn start_music(&mut self, name: &str);
+    fn stop_music(&mut self, name: &str);
+    fn is_music_playing(&self, name: &str) -> bool;
+    // Jeden Frame für laufende Musik, sonst reißt der Stream ab
+    fn update_music(&mut self, name: &str, volume: f32);
+
+    // Alles, was bisher abgespielt wurde. Merkt sich nur NullBackend::recording().
+    #[cfg(test)]
+    fn fired(&self) -> &[SoundCall] {
+        &[]
+    }
+}
+
+pub struct RaylibBackend<'a> {
+    audio_device: &'a RaylibAudio,
+    sounds: HashMap<String, Sound<'a>>,
+    music: HashMap<String, Music<'a>>,
+}
+
+impl<'a> RaylibBackend<'a> {
+    pub fn new(audio_device: &'a RaylibAudio) -> Self {
+        RaylibBackend {
+            audio_device,
+            sounds: HashMap::new(),
+            music: HashMap::new(),
+        }
+    }
+}
+
+impl<'a> AudioBackend for RaylibBackend<'a> {
+    fn load_sound(&mut self, name: &str, path: &str) -> Result<(), String> {
+        let sound = self.audio_device.new_sound(path)?;
+        self.sounds.insert(name.to_string(), sound);
+        Ok(())
+    }
+
+    fn play_sound(&mut self, call: &SoundCall) -> bool {
+        let Some(sound) = self.sounds.get_mut(&call.sound) else {
+            return false;
+        };
+        sound.set_volume(call.volume);
+        sound.set_pitch(call.pitch);
+        sound.set_pan(call.pan);
+        sound.play();
+        true
+    }
+
+    // Musik wird nicht komplett geladen, sondern beim Abspielen gestreamt
+    fn load_music(&mut self, name: &str, path: &str) -> Result<(), String> {
+        let stream = self.audio_device.new_music(path)?;
+        self.music.insert(name.to_string(), stream);
+        Ok(())
+    }
+
+    fn start_music(&mut self, name: &str) {
+        if let Some(stream) = self.music.get_mut(name) {
+            stream.play_stream();
+        }
+    }
+
+    fn stop_music(&mut self, name: &str) {
+        if let Some(stream) = self.music.get_mut(name) {
+            stream.stop_stream();
+        }
+    }
+
+    fn is_music_playing(&self, name: &str) -> bool {
+        self.music.get(name).is_some_and(|stream| stream.is_stream_playing())
+    }
+
+    fn update_music(&mut self, name: &str, volume: f32) {
+        if let Some(stream) = self.music.get_mut(name) {
+            stream.set_volume(volume);
+            stream.update_stream();
+        }
+    }
+}
+
+// Für Rechner ohne Soundkarte (CI, manche VMs) oder mit --no-audio: spielt nichts,
+// schreibt nur ins Log, was gespielt worden wäre. Für Tests gibt es recording(), das merkt sich
+// stattdessen jeden Sound für fired().
+pub struct NullBackend {
+    sounds: HashSet<String>,
+    music: HashSet<String>,
+    playing: HashSet<String>,
+    // None: nur loggen. Im Spiel würde das sonst einfach immer weiter volllaufen.
+    fired: Option<Vec<SoundCall>>,
+}
+
+impl NullBackend {
+    pub fn new() -> Self {
+        println!("AUDIO: null backend, nothing will be audible");
+        NullBackend {
+            sounds: HashSet::new(),
+            music: HashSet::new(),
+            playing: HashSet::new(),
+            fired: None,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn recording() -> Self {
+        NullBackend {
+            fired: Some(Vec::new()),
+            ..NullBackend::new()
+        }
+    }
+}
+
+// Fehlende Dateien sollen auch ohne Soundkarte als fehlend gemeldet werden
+fn check_exists(path: &str) -> Result<(), String> {
+    if Path::new(path).is_file() {
+        Ok(())
+    } else {
+        Err(format!("{} does not exist", path))
+    }
+}
+
+impl AudioBackend for NullBackend {
+    fn load_sound(&mut self, name: &str, path: &str) -> Result<(), String> {
+        check_exists(path)?;
+        self.sounds.insert(name.to_string());
+        Ok(())
+    }
+
+    fn play_sound(&mut self, call: &SoundCall) -> bool {
+        if !self.sounds.contains(&call.sound) {
+            return false;
+        }
+        match self.fired.as_mut() {
+            Some(fired) => fired.push(call.clone()),
+            None => println!(
+                "AUDIO: {} -> {} ({:?}, volume {:.2}, pitch {:.2}, pan {:.2})",
+                call.name, call.sound, call.bus, call.volume, call.pitch, call.pan
+            ),
+        }
+        true
+    }
+
+    fn load_music(&mut self, name: &str, path: &str) -> Result<(), String> {
+        check_exists(path)?;
+        self.music.insert(name.to_string());
+        Ok(())
+    }
+
+    fn start_music(&mut self, name: &str) {
+        if self.music.contains(name) {
+            println!("AUDIO: music {} started", name);
+            self.playing.insert(name.to_string());
+        }
+    }
+
+    fn stop_music(&mut self, name: &str) {
+        if self.playing.remove(name) {
+            println!("AUDIO: music {} stopped", name);
+        }
+    }
+
+    fn is_music_playing(&self, name: &str) -> bool {
+        self.playing.contains(name)
+    }
+
+    fn update_music(&mut self, _name: &str, _volume: f32) {}
+
+    #[cfg(test)]
+    fn fired(&self) -> &[SoundCall] {
+        self.fired.as_deref().unwrap_or(&[])
+    }
+}
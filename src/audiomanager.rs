@@ -1,36 +1,421 @@
+use rand::Rng;
 use raylib::prelude::*;
-use raylib::core::audio::{Sound, RaylibAudio};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::asset_manifest::{AssetManifest, SoundGroupConfig};
+use crate::audio_backend::{AudioBackend, SoundCall};
+
+// So lange dauert das Überblenden zwischen zwei Musikstücken (in Sekunden)
+const CROSSFADE_DURATION: f32 = 1.5;
+// Bis zum Bildschirmrand voll laut, bei so vielen halben Bildschirmen Abstand nicht mehr zu hören
+const HEARING_RANGE: f32 = 3.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bus {
+    // Wirkt auf alles
+    Master,
+    Music,
+    Sfx,
+    Ui,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BusSettings {
+    pub volume: f32,
+    #[serde(default)]
+    pub muted: bool,
+}
+
+impl Default for BusSettings {
+    fn default() -> Self {
+        BusSettings {
+            volume: 1.0,
+            muted: false,
+        }
+    }
+}
+
+// Wird in settings.ron gespeichert
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AudioSettings {
+    #[serde(default)]
+    pub master: BusSettings,
+    #[serde(default)]
+    pub music: BusSettings,
+    #[serde(default)]
+    pub sfx: BusSettings,
+    #[serde(default)]
+    pub ui: BusSettings,
+}
+
+impl AudioSettings {
+    pub fn bus(&self, bus: Bus) -> &BusSettings {
+        match bus {
+            Bus::Master => &self.master,
+            Bus::Music => &self.music,
+            Bus::Sfx => &self.sfx,
+            Bus::Ui => &self.ui,
+        }
+    }
+
+    pub fn bus_mut(&mut self, bus: Bus) -> &mut BusSettings {
+        match bus {
+            Bus::Master => &mut self.master,
+            Bus::Music => &mut self.music,
+            Bus::Sfx => &mut self.sfx,
+            Bus::Ui => &mut self.ui,
+        }
+    }
+
+    // Was am Ende wirklich rauskommt: Master mal Bus, stumm ist stumm
+    pub fn volume(&self, bus: Bus) -> f32 {
+        let own = self.bus(bus);
+        if self.master.muted || own.muted {
+            return 0.0;
+        }
+        match bus {
+            Bus::Master => self.master.volume,
+            _ => self.master.volume * own.volume,
+        }
+    }
+}
+
+// Wo die Kamera gerade hinschaut, danach richten sich Panning und Lautstärke von play_sound_at
+#[derive(Clone, Copy)]
+struct Listener {
+    center: Vector2,
+    half_view: Vector2,
+}
+
+impl Listener {
+    // (Lautstärke 0..1, Pan)
+    fn hear(&self, pos: Vector2) -> (f32, f32) {
+        let offset = pos - self.center;
+        let inner = self.half_view.length().max(1.0);
+        let outer = inner * HEARING_RANGE;
+        let gain = (1.0 - (offset.length() - inner) / (outer - inner)).clamp(0.0, 1.0);
+        // raylib 5.0: 1.0 ist ganz links, 0.0 ganz rechts
+        let pan = 0.5 - 0.5 * (offset.x / (self.half_view.x.max(1.0) * 2.0)).clamp(-1.0, 1.0);
+        (gain, pan)
+    }
+}
+
+struct MusicTrack {
+    path: String,
+    // Aktuelle Lautstärke beim Überblenden (0..1) und wo sie hin soll
+    fade: f32,
+    target: f32,
+}
+
 pub struct AudioManager<'a> {
-    audio_device: &'a RaylibAudio,
-    sounds: HashMap<String, Sound<'a>>,
+    backend: Box<dyn AudioBackend + 'a>,
+    // Name -> Pfad, damit man beim Hot Reload weiß, welcher Sound neu geladen werden muss
+    paths: HashMap<String, String>,
+    groups: HashMap<String, SoundGroupConfig>,
+    listener: Option<Listener>,
+    music: HashMap<String, MusicTrack>,
+    current_music: Option<String>,
+    settings: AudioSettings,
+    missing: Vec<String>,
 }
 
 impl<'a> AudioManager<'a> {
-    pub fn new(audio_device: &'a RaylibAudio) -> Self {
+    pub fn new(backend: Box<dyn AudioBackend + 'a>, settings: AudioSettings) -> Self {
         AudioManager {
-            audio_device,
-            sounds: HashMap::new(),
+            backend,
+            paths: HashMap::new(),
+            groups: HashMap::new(),
+            listener: None,
+            music: HashMap::new(),
+            current_music: None,
+            settings,
+            missing: Vec::new(),
         }
     }
 
     pub fn load_sound(&mut self, name: &str, path: &str) {
-        match self.audio_device.new_sound(path) {
-            Ok(sound) => {
-                self.sounds.insert(name.to_string(), sound);
-            }
+        self.paths.insert(name.to_string(), path.to_string());
+        match self.backend.load_sound(name, path) {
+            Ok(()) => (),
             Err(e) => {
                 eprintln!("Failed to load sound {}: {}", path, e);
+                self.missing.push(path.to_string());
             }
         }
     }
 
+    pub fn load_music(&mut self, name: &str, path: &str) {
+        match self.backend.load_music(name, path) {
+            Ok(()) => {
+                self.music.insert(
+                    name.to_string(),
+                    MusicTrack {
+                        path: path.to_string(),
+                        fade: 0.0,
+                        target: 0.0,
+                    },
+                );
+            }
+            Err(e) => {
+                eprintln!("Failed to load music {}: {}", path, e);
+                self.missing.push(path.to_string());
+            }
+        }
+    }
+
+    pub fn load_manifest(&mut self, manifest: &AssetManifest) {
+        for (name, path) in manifest.sounds.iter() {
+            self.load_sound(name, path);
+        }
+        self.groups
+            .extend(manifest.sound_groups.iter().map(|(name, group)| (name.clone(), group.clone())));
+        for (name, path) in manifest.music.iter() {
+            if self.music.get(name).is_some_and(|track| &track.path == path) {
+                continue;
+            }
+            self.load_music(name, path);
+        }
+    }
+
+    // Lädt alle Sounds und Musikstücke neu, die aus path kommen
+    pub fn reload_path(&mut self, path: &str) {
+        let names: Vec<String> = self
+            .paths
+            .iter()
+            .filter(|(_, p)| p.as_str() == path)
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in names {
+            self.missing.retain(|p| p != path);
+            self.load_sound(&name, path);
+            println!("HOT RELOAD: {} ({})", path, name);
+        }
+
+        let tracks: Vec<String> = self
+            .music
+            .iter()
+            .filter(|(_, track)| track.path == path)
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in tracks {
+            self.load_music(&name, path);
+            // Läuft das Stück gerade, fängt es neu an
+            if self.current_music.as_deref() == Some(name.as_str()) {
+                self.current_music = None;
+                self.play_music(&name);
+            }
+            println!("HOT RELOAD: {} ({})", path, name);
+        }
+    }
+
+    pub fn missing(&self) -> &[String] {
+        &self.missing
+    }
+
+    // Was bisher abgespielt wurde, nur mit NullBackend::recording() gefüllt
+    #[cfg(test)]
+    pub fn fired(&self) -> &[SoundCall] {
+        self.backend.fired()
+    }
+
+    pub fn settings(&self) -> &AudioSettings {
+        &self.settings
+    }
+
+    pub fn change_volume(&mut self, bus: Bus, delta: f32) {
+        let settings = self.settings.bus_mut(bus);
+        settings.volume = (settings.volume + delta).clamp(0.0, 1.0);
+        println!("VOLUME {:?}: {:.0}%", bus, settings.volume * 100.0);
+    }
+
+    pub fn toggle_mute(&mut self, bus: Bus) {
+        let settings = self.settings.bus_mut(bus);
+        settings.muted = !settings.muted;
+        println!("MUTE {:?}: {}", bus, settings.muted);
+    }
+
+    // Jeden Frame mit der Kamera aufrufen, sonst klingt play_sound_at wie play_sound
+    pub fn set_listener(&mut self, center: Vector2, half_view: Vector2) {
+        self.listener = Some(Listener { center, half_view });
+    }
+
+    // Effekte im Spiel (Schritte, Treffer, ...). name darf ein Sound oder eine Gruppe sein.
     pub fn play_sound(&mut self, name: &str) {
-        if let Some(sound) = self.sounds.get_mut(name) {
-            self.sounds.get(name).unwrap().play();
+        self.play_on(name, Bus::Sfx, 1.0, 0.5);
+    }
+
+    // Wie play_sound, aber von pos in der Welt aus: links/rechts und leiser, je weiter weg von der Kamera
+    pub fn play_sound_at(&mut self, name: &str, pos: Vector2) {
+        let Some(listener) = self.listener else {
+            return self.play_sound(name);
+        };
+        let (gain, pan) = listener.hear(pos);
+        if gain > 0.0 {
+            self.play_on(name, Bus::Sfx, gain, pan);
+        }
+    }
+
+    // Menü, Intro und alles, was nicht im Spiel passiert
+    pub fn play_ui_sound(&mut self, name: &str) {
+        self.play_on(name, Bus::Ui, 1.0, 0.5);
+    }
+
+    fn play_on(&mut self, name: &str, bus: Bus, gain: f32, pan: f32) {
+        // Gruppe: zufälliger Sound, Tonhöhe und Lautstärke etwas verwackelt
+        let (sound_name, pitch, gain) = match self.groups.get(name).filter(|group| !group.sounds.is_empty()) {
+            Some(group) => {
+                let mut rng = rand::rng();
+                let variant = &group.sounds[rng.random_range(0..group.sounds.len())];
+                let pitch = group.pitch * (1.0 + rng.random_range(-1.0..=1.0) * group.pitch_jitter);
+                let gain = gain * group.volume * (1.0 - rng.random_range(0.0..=1.0) * group.volume_jitter);
+                (variant.as_str(), pitch, gain)
+            }
+            None => (name, 1.0, gain),
+        };
+
+        let call = SoundCall {
+            name: name.to_string(),
+            sound: sound_name.to_string(),
+            bus,
+            volume: self.settings.volume(bus) * gain,
+            pitch,
+            pan,
+        };
+        if !self.backend.play_sound(&call) {
+            eprintln!("Sound {} not found", sound_name);
+        }
+    }
+
+    // Blendet vom aktuellen Stück zu name über. Läuft name schon, passiert nichts.
+    pub fn play_music(&mut self, name: &str) {
+        if self.current_music.as_deref() == Some(name) {
+            return;
+        }
+        self.stop_music();
+        match self.music.get_mut(name) {
+            Some(track) => {
+                if !self.backend.is_music_playing(name) {
+                    track.fade = 0.0;
+                    self.backend.start_music(name);
+                }
+                track.target = 1.0;
+                self.current_music = Some(name.to_string());
+            }
+            None => eprintln!("Music {} not found", name),
+        }
+    }
+
+    // Musik für eine Szene (intro, gameplay, outro, ...). Steht für die Szene kein Stück im Manifest,
+    // wird das vorige nur ausgeblendet und es bleibt still.
+    pub fn play_scene_music(&mut self, scene: &str) {
+        if self.music.contains_key(scene) {
+            self.play_music(scene);
         } else {
-            eprintln!("Sound {} not found", name);
+            self.stop_music();
         }
     }
-}
\ No newline at end of file
+
+    // Blendet das aktuelle Stück aus
+    pub fn stop_music(&mut self) {
+        if let Some(name) = self.current_music.take() {
+            if let Some(track) = self.music.get_mut(&name) {
+                track.target = 0.0;
+            }
+        }
+    }
+
+    // Jeden Frame aufrufen, sonst reißt der Musik-Stream ab
+    pub fn update(&mut self, delta_time: f32) {
+        let volume = self.settings.volume(Bus::Music);
+        let step = delta_time / CROSSFADE_DURATION;
+        for (name, track) in self.music.iter_mut() {
+            if !self.backend.is_music_playing(name) {
+                continue;
+            }
+            track.fade = if track.fade < track.target {
+                (track.fade + step).min(track.target)
+            } else {
+                (track.fade - step).max(track.target)
+            };
+            if track.fade <= 0.0 && track.target <= 0.0 {
+                self.backend.stop_music(name);
+                continue;
+            }
+            self.backend.update_music(name, track.fade * volume);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_backend::NullBackend;
+
+    // Eigenes kleines Manifest, damit Änderungen an assets/manifest.ron die Tests nicht kaputt machen.
+    // Die Dateien müssen trotzdem existieren, das Null-Backend prüft das.
+    fn manager(settings: AudioSettings) -> AudioManager<'static> {
+        let sounds = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/sounds");
+        let manifest: AssetManifest = ron::from_str(&format!(
+            r#"(
+                sounds: {{
+                    "stone": "{sounds}/stone.ogg",
+                    "sand_step_1": "{sounds}/sand_step_1.wav",
+                    "sand_step_2": "{sounds}/sand_step_2.wav",
+                    "ui": "{sounds}/menu.wav",
+                }},
+                sound_groups: {{
+                    "step_sand": (sounds: ["sand_step_1", "sand_step_2"], pitch_jitter: 0.1, volume_jitter: 0.3),
+                }},
+            )"#
+        ))
+        .unwrap();
+        let mut audio_manager = AudioManager::new(Box::new(NullBackend::recording()), settings);
+        audio_manager.load_manifest(&manifest);
+        assert!(audio_manager.missing().is_empty());
+        audio_manager
+    }
+
+    #[test]
+    fn group_picks_one_of_its_sounds() {
+        let mut audio_manager = manager(AudioSettings::default());
+        audio_manager.play_sound("step_sand");
+        audio_manager.play_ui_sound("ui");
+
+        let fired = audio_manager.fired();
+        assert_eq!(fired.len(), 2);
+        assert_eq!(fired[0].name, "step_sand");
+        assert!(["sand_step_1", "sand_step_2"].contains(&fired[0].sound.as_str()));
+        assert_eq!(fired[0].bus, Bus::Sfx);
+        assert_eq!(fired[1].sound, "ui");
+        assert_eq!(fired[1].bus, Bus::Ui);
+    }
+
+    #[test]
+    fn unknown_sound_is_not_played() {
+        let mut audio_manager = manager(AudioSettings::default());
+        audio_manager.play_sound("does_not_exist");
+        assert!(audio_manager.fired().is_empty());
+    }
+
+    #[test]
+    fn muted_master_silences_everything() {
+        let mut settings = AudioSettings::default();
+        settings.master.muted = true;
+        let mut audio_manager = manager(settings);
+        audio_manager.play_sound("stone");
+        assert_eq!(audio_manager.fired()[0].volume, 0.0);
+    }
+
+    #[test]
+    fn far_away_sounds_are_dropped() {
+        let mut audio_manager = manager(AudioSettings::default());
+        audio_manager.set_listener(Vector2::new(0.0, 0.0), Vector2::new(100.0, 100.0));
+        audio_manager.play_sound_at("stone", Vector2::new(10_000.0, 0.0));
+        audio_manager.play_sound_at("stone", Vector2::new(-50.0, 0.0));
+
+        let fired = audio_manager.fired();
+        assert_eq!(fired.len(), 1);
+        assert!(fired[0].pan > 0.5);
+    }
+}
@@ -1,7 +1,18 @@
 use raylib::prelude::*;
 
+use crate::game_feel::GameFeel;
+
+const MIN_ZOOM: f32 = 0.75;
+const MAX_ZOOM: f32 = 3.0;
+
 pub struct GameCamera {
     pub camera: Camera2D,
+    // Halbe Größe des Bereichs um die Bildmitte, in dem sich der Spieler bewegen kann,
+    // ohne dass die Kamera nachzieht (in Weltkoordinaten)
+    pub dead_zone: Vector2,
+    // Je größer, desto schneller holt die Kamera auf
+    pub smoothing: f32,
+    bounds: Option<Rectangle>,
 }
 
 impl GameCamera {
@@ -16,13 +27,83 @@ impl GameCamera {
             zoom: 1.5,
         };
 
-        GameCamera { camera }
+        GameCamera {
+            camera,
+            dead_zone: Vector2::new(40.0, 30.0),
+            smoothing: 6.0,
+            bounds: None,
+        }
+    }
+
+    // Die Kamera zeigt nie etwas außerhalb von bounds (z.B. das Nichts neben der Insel)
+    pub fn set_bounds(&mut self, bounds: Rectangle) {
+        self.bounds = Some(bounds);
+        self.camera.target = self.clamped(self.camera.target);
+    }
+
+    pub fn zoom(&mut self, steps: f32) {
+        self.camera.zoom = (self.camera.zoom * (1.0 + steps * 0.1)).clamp(MIN_ZOOM, MAX_ZOOM);
     }
 
-    pub fn update_target(&mut self, target_position: Vector2, offset_x: f32, offset_y: f32) {
-        self.camera.target = Vector2 {
+    pub fn update_target(
+        &mut self,
+        target_position: Vector2,
+        offset_x: f32,
+        offset_y: f32,
+        delta_time: f32,
+    ) {
+        let target = Vector2 {
             x: target_position.x + offset_x,
             y: target_position.y + offset_y,
         };
+
+        let mut desired = self.camera.target;
+        let diff = target - desired;
+        if diff.x.abs() > self.dead_zone.x {
+            desired.x = target.x - self.dead_zone.x * diff.x.signum();
+        }
+        if diff.y.abs() > self.dead_zone.y {
+            desired.y = target.y - self.dead_zone.y * diff.y.signum();
+        }
+
+        // Framerate-unabhängiges Lerp
+        let amount = 1.0 - (-self.smoothing * delta_time).exp();
+        self.camera.target = self.clamped(self.camera.target.lerp(desired, amount));
+    }
+
+    // Die Kamera, mit der gezeichnet wird: wie camera, nur mit Screen Shake
+    pub fn view(&self, game_feel: &GameFeel) -> Camera2D {
+        let mut camera = self.camera;
+        camera.offset += game_feel.shake_offset();
+        camera
     }
-}
\ No newline at end of file
+
+    // Halbe Größe des sichtbaren Bereichs in Weltkoordinaten
+    pub fn half_view(&self) -> Vector2 {
+        Vector2::new(
+            self.camera.offset.x / self.camera.zoom,
+            self.camera.offset.y / self.camera.zoom,
+        )
+    }
+
+    fn clamped(&self, target: Vector2) -> Vector2 {
+        let Some(bounds) = self.bounds else {
+            return target;
+        };
+        let half_view = self.half_view();
+
+        let clamp_axis = |value: f32, min: f32, size: f32, half: f32| {
+            // Map kleiner als der Bildschirm: einfach mittig
+            if size <= half * 2.0 {
+                min + size / 2.0
+            } else {
+                value.clamp(min + half, min + size - half)
+            }
+        };
+
+        Vector2::new(
+            clamp_axis(target.x, bounds.x, bounds.width, half_view.x),
+            clamp_axis(target.y, bounds.y, bounds.height, half_view.y),
+        )
+    }
+}
@@ -0,0 +1,209 @@
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::pathfinding::{PathOptions, Pathfinder};
+use crate::tiled_map::TiledMap;
+use crate::trait_collision::Collision;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CreatureKind {
+    Crab,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatureConfig {
+    pub kind: CreatureKind,
+    pub pos: (i32, i32),
+    // Tiles, die der Reihe nach abgelaufen werden, solange der Spieler nicht in Sicht ist
+    #[serde(default)]
+    pub patrol: Vec<(i32, i32)>,
+}
+
+struct CreatureStats {
+    speed: f32,
+    sight_radius: f32, // in Tiles
+    damage: i32,
+    attack_cooldown: f32,
+    size: Vector2,
+    color: Color,
+}
+
+impl CreatureKind {
+    fn stats(&self) -> CreatureStats {
+        match self {
+            CreatureKind::Crab => CreatureStats {
+                speed: 120.0,
+                sight_radius: 4.0,
+                damage: 100,
+                attack_cooldown: 1.0,
+                size: Vector2::new(40.0, 24.0),
+                color: Color::new(220, 80, 50, 255),
+            },
+        }
+    }
+}
+
+#[derive(PartialEq)]
+enum CreatureState {
+    Patrol,
+    Chase,
+}
+
+const REPATH_TIME: f32 = 0.5;
+
+pub struct Creature {
+    pub kind: CreatureKind,
+    pub pos: Vector2, // Mitte der Füße
+    patrol: Vec<(i32, i32)>,
+    patrol_index: usize,
+    state: CreatureState,
+    path: Vec<(i32, i32)>,
+    repath_timer: f32,
+    attack_timer: f32,
+    walk_time: f32,
+}
+
+impl Creature {
+    pub fn new(config: &CreatureConfig, tiled_map: &TiledMap) -> Self {
+        Creature {
+            kind: config.kind,
+            pos: tiled_map.tile_center(config.pos),
+            patrol: config.patrol.clone(),
+            patrol_index: 0,
+            state: CreatureState::Patrol,
+            path: Vec::new(),
+            repath_timer: 0.0,
+            attack_timer: 0.0,
+            walk_time: 0.0,
+        }
+    }
+
+    pub fn hitbox_at(&self, pos: Vector2) -> Rectangle {
+        let size = self.kind.stats().size;
+        Rectangle::new(pos.x - size.x / 2.0, pos.y - size.y, size.x, size.y)
+    }
+
+    pub fn hitbox(&self) -> Rectangle {
+        self.hitbox_at(self.pos)
+    }
+
+    pub fn update(
+        &mut self,
+        delta_time: f32,
+        tiled_map: &TiledMap,
+        pathfinder: &mut Pathfinder,
+        player_feet: Vector2,
+    ) {
+        let stats = self.kind.stats();
+        self.repath_timer -= delta_time;
+        self.attack_timer -= delta_time;
+
+        let tile = tiled_map.tile_at(self.pos);
+        let player_tile = tiled_map.tile_at(player_feet);
+        let distance = (((tile.0 - player_tile.0).pow(2) + (tile.1 - player_tile.1).pow(2)) as f32).sqrt();
+
+        let state = if distance <= stats.sight_radius {
+            CreatureState::Chase
+        } else {
+            CreatureState::Patrol
+        };
+        if state != self.state {
+            self.state = state;
+            self.repath_timer = 0.0;
+        }
+
+        if self.state == CreatureState::Patrol
+            && !self.patrol.is_empty()
+            && tile == self.patrol[self.patrol_index]
+        {
+            self.patrol_index = (self.patrol_index + 1) % self.patrol.len();
+            self.repath_timer = 0.0;
+        }
+
+        if self.repath_timer <= 0.0 {
+            self.repath_timer = REPATH_TIME;
+            let target = match self.state {
+                CreatureState::Chase => Some(player_tile),
+                CreatureState::Patrol => self.patrol.get(self.patrol_index).copied(),
+            };
+            self.path = match target {
+                Some(target) => pathfinder
+                    .find_path(tiled_map, tile, target, &PathOptions::walkable())
+                    .unwrap_or_default(),
+                None => Vec::new(),
+            };
+        }
+
+        // Ziel ist die Mitte vom nächsten Tile, beim Jagen am Ende direkt der Spieler
+        let waypoint = match self.path.first().copied() {
+            Some(next) if next == tile && self.path.len() > 1 => {
+                self.path.remove(0);
+                Some(tiled_map.tile_center(self.path[0]))
+            }
+            Some(next) if next == tile => {
+                self.path.clear();
+                match self.state {
+                    CreatureState::Chase => Some(player_feet),
+                    CreatureState::Patrol => None,
+                }
+            }
+            Some(next) => Some(tiled_map.tile_center(next)),
+            None => None,
+        };
+
+        let Some(waypoint) = waypoint else {
+            return;
+        };
+        let to_waypoint = waypoint - self.pos;
+        if to_waypoint.length() < 1.0 {
+            return;
+        }
+        let step = (stats.speed * delta_time).min(to_waypoint.length());
+        let delta = to_waypoint.normalized().scale_by(step);
+        let old_pos = self.pos;
+        self.pos = tiled_map.slide(old_pos, delta, |pos| self.hitbox_at(pos));
+        if self.pos != old_pos {
+            self.walk_time += delta_time;
+        }
+    }
+
+    // Gibt den Schaden zurück, den der Spieler gerade bekommt
+    pub fn attack(&mut self, player_rect: &Rectangle) -> i32 {
+        if self.attack_timer > 0.0 || !self.collision_with_rec(player_rect) {
+            return 0;
+        }
+        let stats = self.kind.stats();
+        self.attack_timer = stats.attack_cooldown;
+        stats.damage
+    }
+
+    pub fn render(&self, d: &mut RaylibMode2D<'_, RaylibDrawHandle>) {
+        let stats = self.kind.stats();
+        let rect = self.hitbox();
+        let wobble = (self.walk_time * 12.0).sin() * 2.0;
+        let center = Vector2::new(rect.x + rect.width / 2.0, rect.y + rect.height / 2.0 + wobble);
+
+        // Keine Krabben-Sprites, also malen wir eine
+        match self.kind {
+            CreatureKind::Crab => {
+                d.draw_ellipse(
+                    center.x as i32,
+                    center.y as i32,
+                    rect.width / 2.0,
+                    rect.height / 2.0,
+                    stats.color,
+                );
+                d.draw_circle_v(Vector2::new(rect.x - 2.0, center.y - 6.0), 6.0, stats.color);
+                d.draw_circle_v(Vector2::new(rect.x + rect.width + 2.0, center.y - 6.0), 6.0, stats.color);
+                d.draw_circle_v(Vector2::new(center.x - 7.0, rect.y + wobble), 3.0, Color::BLACK);
+                d.draw_circle_v(Vector2::new(center.x + 7.0, rect.y + wobble), 3.0, Color::BLACK);
+            }
+        }
+    }
+}
+
+impl Collision for Creature {
+    fn collision_with_rec(&self, other: &Rectangle) -> bool {
+        self.hitbox().check_collision_recs(other)
+    }
+}
@@ -0,0 +1,127 @@
+use raylib::prelude::*;
+
+use crate::particles::{EmitterConfig, ParticleShape, ParticleSystem};
+use crate::texture_atlas::SpriteRef;
+use crate::tiled_map::Tile;
+
+const MAX_SHAKE_OFFSET: f32 = 14.0;
+// Wie schnell das Wackeln abklingt (Trauma pro Sekunde)
+const TRAUMA_DECAY: f32 = 1.8;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Material {
+    Wood,
+    Stone,
+    Sand,
+}
+
+struct Impact {
+    trauma: f32,
+    hit_stop_frames: u32,
+    debris: usize,
+    colors: [Color; 2],
+}
+
+impl Material {
+    pub fn of(tile: &Tile) -> Option<Material> {
+        match tile {
+            Tile::Static(2, _) => Some(Material::Wood),
+            Tile::Static(7, _) => Some(Material::Stone),
+            Tile::Static(18, _) => Some(Material::Sand),
+            _ => None,
+        }
+    }
+
+    fn impact(&self) -> Impact {
+        match self {
+            Material::Stone => Impact {
+                trauma: 0.6,
+                hit_stop_frames: 6,
+                debris: 14,
+                colors: [Color::new(120, 120, 120, 255), Color::new(170, 170, 165, 255)],
+            },
+            Material::Wood => Impact {
+                trauma: 0.35,
+                hit_stop_frames: 4,
+                debris: 12,
+                colors: [Color::new(120, 80, 40, 255), Color::new(170, 120, 60, 255)],
+            },
+            Material::Sand => Impact {
+                trauma: 0.15,
+                hit_stop_frames: 2,
+                debris: 20,
+                colors: [Color::new(230, 205, 150, 255), Color::new(200, 170, 110, 255)],
+            },
+        }
+    }
+
+    // Splitter, Holzspäne oder Sand, je nach Material. sprite ist das Sprite vom getroffenen Tile.
+    pub fn debris(&self, color: Color, sprite: Option<SpriteRef>) -> EmitterConfig {
+        let (shape, size) = match (self, sprite) {
+            (Material::Sand, _) => (ParticleShape::Circle, (2.0, 5.0)),
+            // Steinbrocken: der Stein selbst, ganz klein
+            (Material::Stone, Some(sprite)) => (ParticleShape::Texture(sprite), (6.0, 12.0)),
+            _ => (ParticleShape::Square, (2.0, 5.0)),
+        };
+        EmitterConfig {
+            count: self.impact().debris / 2,
+            lifetime: (0.3, 0.7),
+            speed: (80.0, 260.0),
+            angle: (-std::f32::consts::PI, 0.0),
+            gravity: 600.0,
+            size,
+            start_color: color,
+            end_color: color.fade(0.0),
+            shape,
+            ..Default::default()
+        }
+    }
+}
+
+// Screen Shake und Hit-Stop beim Zuschlagen. GameCamera holt sich hier den Shake ab.
+pub struct GameFeel {
+    trauma: f32,
+    hit_stop_frames: u32,
+    time: f32,
+}
+
+impl GameFeel {
+    pub fn new() -> Self {
+        GameFeel {
+            trauma: 0.0,
+            hit_stop_frames: 0,
+            time: 0.0,
+        }
+    }
+
+    pub fn impact(&mut self, material: Material, pos: Vector2, sprite: Option<SpriteRef>, particles: &mut ParticleSystem) {
+        let impact = material.impact();
+        self.trauma = (self.trauma + impact.trauma).min(1.0);
+        self.hit_stop_frames = self.hit_stop_frames.max(impact.hit_stop_frames);
+
+        for color in impact.colors {
+            particles.burst(&material.debris(color, sprite), pos);
+        }
+    }
+
+    // Gibt die Zeit zurück, mit der das Spiel weiterlaufen soll (0 während Hit-Stop)
+    pub fn update(&mut self, frame_time: f32) -> f32 {
+        self.time += frame_time;
+        self.trauma = (self.trauma - TRAUMA_DECAY * frame_time).max(0.0);
+
+        if self.hit_stop_frames > 0 {
+            self.hit_stop_frames -= 1;
+            return 0.0;
+        }
+        frame_time
+    }
+
+    pub fn shake_offset(&self) -> Vector2 {
+        // trauma² fühlt sich besser an als linear
+        let shake = self.trauma * self.trauma * MAX_SHAKE_OFFSET;
+        Vector2::new(
+            (self.time * 47.0).sin() * (self.time * 13.0).cos() * shake,
+            (self.time * 59.0).cos() * (self.time * 17.0).sin() * shake,
+        )
+    }
+}
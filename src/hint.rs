@@ -0,0 +1,167 @@
+use raylib::prelude::*;
+use std::collections::HashMap;
+
+use crate::item::{Item, ItemType};
+use crate::pathfinding::{PathOptions, Pathfinder, TilePos, tile_cost};
+use crate::tiled_map::{Tags, Tile, TiledMap};
+
+pub const HINT_COST: i32 = 50;
+const HINT_DURATION: f32 = 4.0;
+// Ein Hindernis wegzuhauen dauert länger als drumherum zu laufen
+const DESTROY_COST: u32 = 3;
+
+pub struct Hint {
+    path: Vec<TilePos>,
+    timer: f32,
+}
+
+impl Hint {
+    pub fn new() -> Self {
+        Hint {
+            path: Vec::new(),
+            timer: 0.0,
+        }
+    }
+
+    pub fn show(&mut self, path: Vec<TilePos>) {
+        self.path = path;
+        self.timer = HINT_DURATION;
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        self.timer = (self.timer - delta_time).max(0.0);
+    }
+
+    pub fn render(&self, d: &mut RaylibMode2D<'_, RaylibDrawHandle>, tiled_map: &TiledMap) {
+        if self.timer <= 0.0 {
+            return;
+        }
+        // Die letzte Sekunde ausblenden
+        let alpha = self.timer.min(1.0);
+
+        for step in self.path.windows(2) {
+            let from = tiled_map.tile_center(step[0]);
+            let to = tiled_map.tile_center(step[1]);
+            let direction = to - from;
+            let rotation = direction.y.atan2(direction.x).to_degrees();
+            d.draw_poly(from.lerp(to, 0.5), 3, 12.0, rotation, Color::GOLD.fade(alpha));
+        }
+        if let Some(last) = self.path.last() {
+            d.draw_circle_v(tiled_map.tile_center(*last), 10.0, Color::GOLD.fade(alpha));
+        }
+    }
+}
+
+// Weg zum nächsten sinnvollen Ziel: fehlende Teile, sonst das Ziel-Tile. Wenn der Weg von einem
+// Hindernis versperrt ist, das der Spieler nicht (mehr) kaputt machen kann, zuerst zum passenden Tool.
+// tools sind die Tools des Spielers mit ihren übrigen Benutzungen.
+pub fn plan_route(
+    tiled_map: &TiledMap,
+    pathfinder: &mut Pathfinder,
+    start: TilePos,
+    tools: &[(ItemType, i32)],
+    items: &[Item],
+    parts_missing: bool,
+) -> Option<Vec<TilePos>> {
+    // Wie viele Hindernisse jeder Sorte man mit allen Tools zusammen noch schafft
+    let mut uses: HashMap<i32, i32> = HashMap::new();
+    for (tool, uses_left) in tools {
+        if let Some(id) = tool.destroys() {
+            *uses.entry(id).or_insert(0) += (*uses_left).max(0);
+        }
+    }
+    let destroyable: Vec<i32> = uses.iter().filter(|(_, n)| **n > 0).map(|(id, _)| *id).collect();
+    // Drumherum laufen geht immer, auch wenn der billigere Weg mehr Hindernisse hat, als die Tools schaffen
+    let reachable = [
+        PathOptions::only_destroying(destroyable, DESTROY_COST),
+        PathOptions::walkable(),
+    ];
+    let affordable = |path: &[TilePos]| {
+        let mut needed: HashMap<i32, i32> = HashMap::new();
+        for id in obstacles(tiled_map, path) {
+            *needed.entry(id).or_insert(0) += 1;
+        }
+        needed.iter().all(|(id, n)| uses.get(id).is_some_and(|left| left >= n))
+    };
+
+    let item_tiles = |item_type: ItemType| -> Vec<TilePos> {
+        items
+            .iter()
+            .filter(|item| item.item_type == item_type)
+            .map(|item| tiled_map.tile_at(item.center()))
+            .collect()
+    };
+
+    let mut targets = tiled_map.find_tiles_with_tag(Tags::Goal);
+    if parts_missing {
+        let parts = item_tiles(ItemType::Gear);
+        if !parts.is_empty() {
+            targets = parts;
+        }
+    }
+
+    if let Some(path) = cheapest_path(tiled_map, pathfinder, start, &targets, &reachable, &affordable) {
+        return Some(path);
+    }
+
+    // Blockiert: das erste Hindernis auf dem Weg, für das die Tools nicht reichen. Dafür braucht man ein Tool.
+    let blocked_path = cheapest_path(
+        tiled_map,
+        pathfinder,
+        start,
+        &targets,
+        &[PathOptions::through_destroyables(DESTROY_COST)],
+        &|_| true,
+    )?;
+    let mut left = uses.clone();
+    let obstacle = obstacles(tiled_map, &blocked_path).into_iter().find(|id| {
+        let left = left.entry(*id).or_insert(0);
+        *left -= 1;
+        *left < 0
+    })?;
+
+    let tool_tiles: Vec<TilePos> = [ItemType::Axe, ItemType::Pickaxe, ItemType::Shovel]
+        .into_iter()
+        .filter(|tool| tool.destroys() == Some(obstacle))
+        .flat_map(item_tiles)
+        .collect();
+    cheapest_path(tiled_map, pathfinder, start, &tool_tiles, &reachable, &affordable)
+}
+
+// Zerstörbare Hindernisse (Tile-ID) auf dem Weg, in der Reihenfolge, in der man auf sie trifft
+fn obstacles(tiled_map: &TiledMap, path: &[TilePos]) -> Vec<i32> {
+    path.iter()
+        .flat_map(|pos| tiled_map.tiles_at(pos.0, pos.1))
+        .filter_map(|tile| match tile {
+            Tile::Static(id, tags) if tags.contains(&Tags::Barrier) && tags.contains(&Tags::Destroyable) => Some(*id),
+            _ => None,
+        })
+        .collect()
+}
+
+// Der Weg mit den kleinsten A*-Kosten (nicht der mit den wenigsten Tiles), den usable durchlässt
+fn cheapest_path(
+    tiled_map: &TiledMap,
+    pathfinder: &mut Pathfinder,
+    start: TilePos,
+    targets: &[TilePos],
+    options: &[PathOptions],
+    usable: &dyn Fn(&[TilePos]) -> bool,
+) -> Option<Vec<TilePos>> {
+    let mut best: Option<(u32, Vec<TilePos>)> = None;
+    for options in options {
+        for target in targets {
+            let Some(path) = pathfinder.find_path(tiled_map, start, *target, options) else {
+                continue;
+            };
+            if !usable(&path) {
+                continue;
+            }
+            let cost: u32 = path.iter().skip(1).filter_map(|tile| tile_cost(tiled_map, *tile, options)).sum();
+            if best.as_ref().is_none_or(|(best_cost, _)| cost < *best_cost) {
+                best = Some((cost, path));
+            }
+        }
+    }
+    best.map(|(_, path)| path)
+}
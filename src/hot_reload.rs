@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// Wie oft assets/ nach Änderungen abgesucht wird (in Sekunden)
+const SCAN_INTERVAL: f32 = 0.5;
+
+// Schaut regelmäßig, ob sich Dateien unter dir geändert haben (neu, geändert oder gelöscht).
+// Kein Dateisystem-Event, nur Änderungszeiten vergleichen, reicht für die paar Dateien.
+pub struct AssetWatcher {
+    dir: PathBuf,
+    enabled: bool,
+    timer: f32,
+    modified: HashMap<PathBuf, SystemTime>,
+    changed: Vec<PathBuf>,
+}
+
+impl AssetWatcher {
+    pub fn new(dir: &str, enabled: bool) -> Self {
+        let mut watcher = AssetWatcher {
+            dir: PathBuf::from(dir),
+            enabled,
+            timer: 0.0,
+            modified: HashMap::new(),
+            changed: Vec::new(),
+        };
+        if enabled {
+            println!("HOT RELOAD: watching {}", dir);
+            watcher.modified = watcher.scan();
+        }
+        watcher
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        if !self.enabled {
+            return;
+        }
+        self.timer += delta_time;
+        if self.timer < SCAN_INTERVAL {
+            return;
+        }
+        self.timer = 0.0;
+
+        let current = self.scan();
+        for (path, time) in current.iter() {
+            if self.modified.get(path) != Some(time) {
+                self.mark_changed(path);
+            }
+        }
+        for path in self.modified.keys() {
+            if !current.contains_key(path) && !self.changed.contains(path) {
+                self.changed.push(path.clone());
+            }
+        }
+        self.modified = current;
+    }
+
+    fn mark_changed(&mut self, path: &Path) {
+        if !self.changed.iter().any(|p| p == path) {
+            self.changed.push(path.to_path_buf());
+        }
+    }
+
+    // Nimmt nur die Änderungen raus, die filter haben will. Der Rest bleibt für später liegen,
+    // z.B. geänderte Texturen, während gerade das Intro läuft.
+    pub fn take(&mut self, filter: impl Fn(&Path) -> bool) -> Vec<PathBuf> {
+        let (taken, rest) = std::mem::take(&mut self.changed)
+            .into_iter()
+            .partition(|path| filter(path));
+        self.changed = rest;
+        taken
+    }
+
+    fn scan(&self) -> HashMap<PathBuf, SystemTime> {
+        let mut found = HashMap::new();
+        let mut dirs = vec![self.dir.clone()];
+        while let Some(dir) = dirs.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                if metadata.is_dir() {
+                    dirs.push(path);
+                } else if let Ok(time) = metadata.modified() {
+                    found.insert(path, time);
+                }
+            }
+        }
+        found
+    }
+}
+
+// Pfade kommen von read_dir als "assets/foo.png", im Manifest stehen sie genauso drin
+pub fn asset_key(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
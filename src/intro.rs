@@ -1,59 +1,199 @@
 use raylib::prelude::*;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::str::Chars;
 
-use crate::audiomanager::{self, AudioManager};
+use crate::audiomanager::AudioManager;
+use crate::hot_reload::AssetWatcher;
+use crate::localization::Localization;
+use crate::markup::{self, Cue, Layout, Page};
+
+// Liegt die Datei im Ordner, bestimmt sie die Reihenfolge der Seiten, z.B. ["start.txt", "insel.txt"]
+const PAGE_LIST: &str = "pages.ron";
+const FONT_SIZE: i32 = 22;
+// Abstand zwischen Bild und Text
+const IMAGE_GAP: i32 = 30;
+// Zeichen pro Sekunde, wenn nichts anderes eingestellt ist
+pub const DEFAULT_TEXT_SPEED: f32 = 40.0;
+
+// Die fertig umgebrochene aktuelle Seite. Neu berechnet wird nur bei anderer Seite oder Fenstergröße.
+struct CachedLayout {
+    page: usize,
+    screen: (i32, i32),
+    layout: Layout,
+}
 
 pub struct IntroSequence {
-    pub(crate) files_content: Vec<String>,
+    pub(crate) dir: String,
+    pub(crate) pages: Vec<Page>,
+    // Typewriter-Geschwindigkeit in Zeichen pro Sekunde
+    pub chars_per_second: f32,
+    layout: Option<CachedLayout>,
+    // None: konnte nicht geladen werden, nicht jeden Frame nochmal probieren
+    images: HashMap<String, Option<Texture2D>>,
 }
 
 impl IntroSequence {
     pub fn new(intro_dir: &str) -> Result<Self, String> {
-        let mut files_content = Vec::new();
-        
-        match fs::read_dir(intro_dir) {
-            Ok(entries) => {
-                for entry in entries {
-                    println!("{:?}", entry);
-                    if let Ok(entry) = entry {
-                        let path = entry.path();
-                        if let Some(content) = Self::read_file_content(&path) {
-                            files_content.push(content);
-                        }
-                    }
-                }
-                Ok(Self { files_content })
-            },
-            Err(e) => Err(format!("Failed to read intro directory: {}", e)),
+        let mut pages = Vec::new();
+        for path in Self::page_files(intro_dir)? {
+            match fs::read_to_string(&path) {
+                Ok(content) => pages.push(markup::parse(&content)),
+                Err(why) => println!("couldn't read {}: {}", path.display(), why),
+            }
         }
+        let mut intro = Self::empty(intro_dir);
+        intro.pages = pages;
+        Ok(intro)
+    }
+
+    // Ohne Seiten, z.B. wenn der Ordner fehlt. play() ist dann sofort fertig.
+    pub fn empty(intro_dir: &str) -> Self {
+        IntroSequence {
+            dir: intro_dir.to_string(),
+            pages: Vec::new(),
+            chars_per_second: DEFAULT_TEXT_SPEED,
+            layout: None,
+            images: HashMap::new(),
+        }
+    }
+
+    // Aus pages.ron, sonst alle .txt im Ordner natürlich sortiert (page_2 vor page_10)
+    fn page_files(intro_dir: &str) -> Result<Vec<PathBuf>, String> {
+        let dir = Path::new(intro_dir);
+        let list = dir.join(PAGE_LIST);
+        if list.is_file() {
+            let s = fs::read_to_string(&list).map_err(|why| format!("couldn't read {}: {}", list.display(), why))?;
+            let names: Vec<String> = ron::from_str(s.as_str()).map_err(|why| format!("{}: {}", list.display(), why))?;
+            return Ok(names.iter().map(|name| dir.join(name)).collect());
+        }
+
+        let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read intro directory: {}", e))?;
+        let mut files: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+            .collect();
+        files.sort_by(|a, b| natural_cmp(&a.to_string_lossy(), &b.to_string_lossy()));
+        Ok(files)
     }
-    
-    fn read_file_content(path: &PathBuf) -> Option<String> {
-        if let Some(path_str) = path.to_str() {
-            match fs::read_to_string(path_str) {
-                Ok(content) => Some(content),
-                Err(_) => None,
+
+    // Text neu einlesen, wenn sich im Ordner was geändert hat. Kaputter Ordner: alten Text behalten.
+    pub fn reload(&mut self) {
+        match IntroSequence::new(&self.dir) {
+            Ok(reloaded) => {
+                println!("HOT RELOAD: {}", self.dir);
+                self.pages = reloaded.pages;
+                self.layout = None;
+                self.images.clear();
             }
-        } else {
-            None
+            Err(err) => println!("Failed to reload {}: {}", self.dir, err),
+        }
+    }
+
+    fn update_layout(&mut self, rl: &RaylibHandle, page: usize) {
+        let screen = (rl.get_screen_width(), rl.get_screen_height());
+        if self.layout.as_ref().is_some_and(|cached| cached.page == page && cached.screen == screen) {
+            return;
         }
+        self.layout = Some(CachedLayout {
+            page,
+            screen,
+            layout: markup::layout(rl, &self.pages[page], screen.0 - 80, FONT_SIZE, self.chars_per_second),
+        });
     }
-    
-    pub fn play(&self, rl: &mut RaylibHandle, thread: &RaylibThread, audiomanager: &mut AudioManager) -> bool {
-        let mut last_skip_time = 0.0;
-        
-        'content: for content in &self.files_content {
-            let mut show_skip_message = false;
+
+    fn load_image(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, path: &str) {
+        if self.images.contains_key(path) {
+            return;
+        }
+        let texture = match rl.load_texture(thread, path) {
+            Ok(texture) => Some(texture),
+            Err(why) => {
+                println!("couldn't load {}: {}", path, why);
+                None
+            }
+        };
+        self.images.insert(path.to_string(), texture);
+    }
+
+    // Spielt die Seiten wie eine Zwischensequenz ab: der Text wird getippt, SPACE zeigt die ganze Seite,
+    // nochmal SPACE blättert weiter. Seiten mit [auto] blättern von selbst.
+    pub fn play(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        audiomanager: &mut AudioManager,
+        watcher: &mut AssetWatcher,
+        localization: &Localization,
+        music: &str,
+    ) -> bool {
+        audiomanager.play_scene_music(music);
+        let mut page = 0;
+
+        'content: while page < self.pages.len() {
+            let page_start = rl.get_time();
+            let mut skipped = false;
+            let mut revealed_at: Option<f64> = None;
+            let mut next_cue = 0;
             while !rl.window_should_close() {
+                audiomanager.update(rl.get_frame_time());
+                watcher.update(rl.get_frame_time());
+                let dir = Path::new(&self.dir);
+                if !watcher.take(|path| path.starts_with(dir)).is_empty() {
+                    self.reload();
+                    // Die Seite fängt von vorne an, Cues und Zeit vom alten Text passen nicht mehr
+                    continue 'content;
+                }
+
+                self.update_layout(rl, page);
+                let image = self.pages[page].image.clone();
+                if let Some(path) = &image {
+                    self.load_image(rl, thread, path);
+                }
+                let layout = &self.layout.as_ref().unwrap().layout;
+
                 let current_time = rl.get_time();
-                if current_time - last_skip_time >= 3.0 {
-                    show_skip_message = true;
-                    if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
-                        last_skip_time = current_time;
-                        audiomanager.play_sound("ui");
+                let page_time = match skipped {
+                    true => layout.duration,
+                    false => (current_time - page_start) as f32,
+                };
+                while next_cue < layout.cues.len() && layout.cues[next_cue].0 <= page_time {
+                    match &layout.cues[next_cue].1 {
+                        Cue::Sound(name) => audiomanager.play_sound(name),
+                        Cue::Music(name) => audiomanager.play_music(name),
+                    }
+                    next_cue += 1;
+                }
+                if page_time >= layout.duration && revealed_at.is_none() {
+                    revealed_at = Some(current_time);
+                }
+
+                let auto_advance = self.pages[page]
+                    .auto_advance
+                    .zip(revealed_at)
+                    .is_some_and(|(seconds, at)| current_time - at >= seconds as f64);
+                if auto_advance {
+                    page += 1;
+                    continue 'content;
+                }
+                if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
+                    if revealed_at.is_some() {
+                        audiomanager.play_ui_sound("ui");
+                        page += 1;
                         continue 'content;
                     }
+                    // Übersprungene Sounds nicht alle auf einmal, aber die Musik soll stimmen
+                    skipped = true;
+                    for (_, cue) in layout.cues[next_cue..].iter() {
+                        if let Cue::Music(name) = cue {
+                            audiomanager.play_music(name);
+                        }
+                    }
+                    next_cue = layout.cues.len();
                 }
 
                 let window_height = rl.get_screen_height();
@@ -62,62 +202,71 @@ impl IntroSequence {
                 let mut d = rl.begin_drawing(&thread);
                 d.clear_background(Color::BLACK);
 
-                let font_size = 22;
-                let screen_width = window_width;
-                let line_height = font_size + 5;
-                let max_width = screen_width - 80; 
-                
-                let words: Vec<&str> = content.split_whitespace().collect();
-                let mut lines: Vec<String> = Vec::new();
-                let mut current_line = String::new();
-                
-                for word in words {
-                    let test_line = if current_line.is_empty() {
-                        word.to_string()
-                    } else {
-                        format!("{} {}", current_line, word)
-                    };
-                    
-                    let test_width = d.measure_text(&test_line, font_size);
-                    if (test_width > max_width && !current_line.is_empty()) || word == "\\n" {
-                        lines.push(current_line);
-                        if word == "\\n" {
-                            current_line = String::new();
-                        } else {
-                            current_line = word.to_string();
-                        }
-                    } else {
-                        current_line = test_line;
-                    }
-                }
-                
-                if !current_line.is_empty() {
-                    lines.push(current_line);
+                // Bild und Text zusammen mittig
+                let texture = image.and_then(|path| self.images.get(&path)).and_then(|texture| texture.as_ref());
+                let image_scale = texture.map_or(1, |texture| ((window_height / 3) / texture.height.max(1)).clamp(1, 8));
+                let image_height = texture.map_or(0, |texture| texture.height * image_scale + IMAGE_GAP);
+                let start_y = (window_height - (image_height + layout.height)) / 2;
+
+                if let Some(texture) = texture {
+                    let x = (window_width - texture.width * image_scale) / 2;
+                    d.draw_texture_ex(texture, Vector2::new(x as f32, start_y as f32), 0.0, image_scale as f32, Color::WHITE);
                 }
-                
-                let start_y = (window_height - (lines.len() as i32 * line_height)) / 2;
-                let total_text_height = lines.len() as i32 * line_height;
-
-                for (i, line) in lines.iter().enumerate() {
-                    let line_width = d.measure_text(line, font_size);
-                    let x = (screen_width - line_width) / 2;
-                    let y = start_y + (i as i32 * line_height);
-                    d.draw_text(line, x, y, font_size, Color::WHITE);
-                    if show_skip_message {
-                        let skip_text = "Press SPACE to skip";
-                        let skip_width = d.measure_text(skip_text, font_size);
-                        let skip_x = (screen_width - skip_width) / 2;
-                        let skip_y = start_y + total_text_height + 20;
-                        d.draw_text(skip_text, skip_x, skip_y, font_size, Color::WHITE);
-                    }
-                        d.draw_text(line, x, y, font_size, Color::WHITE);
+
+                let text_y = start_y + image_height;
+                for word in layout.words.iter() {
+                    let text = word.visible_text(page_time);
+                    if !text.is_empty() {
+                        d.draw_text(text, 40 + word.x, text_y + word.y, FONT_SIZE, word.color);
                     }
                 }
+
+                // Hinweis erst, wenn die Seite fertig ist, dann blinkend
+                if revealed_at.is_some() && (current_time * 2.0) as i64 % 2 == 0 {
+                    let continue_text = localization.get("intro.continue");
+                    let continue_width = d.measure_text(continue_text, FONT_SIZE);
+                    let continue_x = (window_width - continue_width) / 2;
+                    let continue_y = text_y + layout.height + 20;
+                    d.draw_text(continue_text, continue_x, continue_y, FONT_SIZE, Color::GRAY);
+                }
             }
-            
+
             if rl.window_should_close() {
                 return false;
             }
-            true
         }
+        true
     }
+}
+
+// Wie normaler Stringvergleich, aber Zahlen werden als Zahlen verglichen: "page_2" < "page_10"
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        let ordering = match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                take_number(&mut a).cmp(&take_number(&mut b))
+            }
+            (Some(x), Some(y)) => {
+                a.next();
+                b.next();
+                x.cmp(&y)
+            }
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+fn take_number(chars: &mut Peekable<Chars>) -> u64 {
+    let mut number: u64 = 0;
+    while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+        number = number.saturating_mul(10).saturating_add(digit as u64);
+        chars.next();
+    }
+    number
+}
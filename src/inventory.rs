@@ -0,0 +1,197 @@
+use raylib::prelude::*;
+
+use crate::player::Orientation;
+use crate::texture_atlas::TextureAtlas;
+use crate::tool::Tool;
+
+pub const BACKPACK_SLOTS: usize = 4;
+
+const SLOT_SIZE: i32 = 56;
+const SLOT_PADDING: i32 = 8;
+
+// Zwei Hände + Rucksack. Die Hände sind das, was der Spieler benutzen kann,
+// der Rucksack ist nur Stauraum.
+pub struct Inventory {
+    pub left: Option<Tool>,
+    pub right: Option<Tool>,
+    pub backpack: Vec<Option<Tool>>,
+    pub selected: usize,
+}
+
+impl Inventory {
+    pub fn new(backpack_slots: usize) -> Self {
+        Inventory {
+            left: None,
+            right: None,
+            backpack: vec![None; backpack_slots],
+            selected: 0,
+        }
+    }
+
+    // Erst die Hände füllen, dann den Rucksack. Gibt das Tool zurück, wenn kein Platz mehr ist.
+    pub fn add(&mut self, tool: Tool) -> Result<(), Tool> {
+        if self.left.is_none() {
+            self.left = Some(tool);
+        } else if self.right.is_none() {
+            self.right = Some(tool);
+        } else if let Some(slot) = self.backpack.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(tool);
+        } else {
+            return Err(tool);
+        }
+        self.fix_orientations();
+        Ok(())
+    }
+
+    pub fn hand(&mut self, orientation: &Orientation) -> Option<&mut Tool> {
+        match orientation {
+            Orientation::Left => self.left.as_mut(),
+            Orientation::Right => self.right.as_mut(),
+        }
+    }
+
+    pub fn take_hand(&mut self, orientation: &Orientation) -> Option<Tool> {
+        match orientation {
+            Orientation::Left => self.left.take(),
+            Orientation::Right => self.right.take(),
+        }
+    }
+
+    pub fn tools(&self) -> impl Iterator<Item = &Tool> {
+        self.left
+            .iter()
+            .chain(self.right.iter())
+            .chain(self.backpack.iter().flatten())
+    }
+
+    pub fn hands_mut(&mut self) -> impl Iterator<Item = &mut Tool> {
+        self.left.iter_mut().chain(self.right.iter_mut())
+    }
+
+    // Alle Tools rücken einen Platz weiter: rechts -> links, Rucksack -> rechts, links -> Rucksack
+    pub fn cycle(&mut self) {
+        // Ohne Rucksack einfach die Hände tauschen, wie früher
+        if self.backpack.iter().all(|slot| slot.is_none()) {
+            std::mem::swap(&mut self.left, &mut self.right);
+            self.fix_orientations();
+            return;
+        }
+
+        let mut tools: Vec<Tool> = self
+            .left
+            .take()
+            .into_iter()
+            .chain(self.right.take())
+            .chain(self.backpack.iter_mut().filter_map(|slot| slot.take()))
+            .collect();
+        if tools.len() > 1 {
+            tools.rotate_left(1);
+        }
+
+        let mut tools = tools.into_iter();
+        self.left = tools.next();
+        self.right = tools.next();
+        for slot in self.backpack.iter_mut() {
+            *slot = tools.next();
+        }
+        self.fix_orientations();
+    }
+
+    pub fn select_slot(&mut self, slot: usize) {
+        if slot < self.backpack.len() {
+            self.selected = slot;
+        }
+    }
+
+    pub fn swap_with_backpack(&mut self, orientation: &Orientation) {
+        let Some(slot) = self.backpack.get_mut(self.selected) else {
+            return;
+        };
+        match orientation {
+            Orientation::Left => std::mem::swap(&mut self.left, slot),
+            Orientation::Right => std::mem::swap(&mut self.right, slot),
+        }
+        self.fix_orientations();
+    }
+
+    pub fn remove_broken(&mut self) {
+        if self.left.as_ref().is_some_and(|t| t.is_broken()) {
+            self.left = None;
+        }
+        if self.right.as_ref().is_some_and(|t| t.is_broken()) {
+            self.right = None;
+        }
+    }
+
+    fn fix_orientations(&mut self) {
+        if let Some(l) = &mut self.left {
+            l.set_orientation(Orientation::Left);
+        }
+        if let Some(r) = &mut self.right {
+            r.set_orientation(Orientation::Right);
+        }
+    }
+
+    pub fn draw_hud(&self, d: &mut RaylibDrawHandle, atlas: &TextureAtlas, active: &Orientation) {
+        let y = d.get_screen_height() - SLOT_SIZE - SLOT_PADDING * 2;
+        let mut x = SLOT_PADDING * 2;
+
+        let hands = [
+            (&self.left, matches!(active, Orientation::Left)),
+            (&self.right, matches!(active, Orientation::Right)),
+        ];
+        for (tool, is_active) in hands {
+            Self::draw_slot(d, atlas, x, y, tool, if is_active { Color::GOLD } else { Color::WHITE });
+            x += SLOT_SIZE + SLOT_PADDING;
+        }
+
+        x += SLOT_PADDING * 2;
+        for (index, tool) in self.backpack.iter().enumerate() {
+            let border = if index == self.selected {
+                Color::SKYBLUE
+            } else {
+                Color::GRAY
+            };
+            Self::draw_slot(d, atlas, x, y, tool, border);
+            d.draw_text(
+                format!("{}", index + 1).as_str(),
+                x + 4,
+                y + 4,
+                10,
+                Color::LIGHTGRAY,
+            );
+            x += SLOT_SIZE + SLOT_PADDING;
+        }
+    }
+
+    fn draw_slot(d: &mut RaylibDrawHandle, atlas: &TextureAtlas, x: i32, y: i32, tool: &Option<Tool>, border: Color) {
+        d.draw_rectangle(x, y, SLOT_SIZE, SLOT_SIZE, Color::BLACK.fade(0.5));
+        d.draw_rectangle_lines_ex(
+            Rectangle::new(x as f32, y as f32, SLOT_SIZE as f32, SLOT_SIZE as f32),
+            2.0,
+            border,
+        );
+
+        if let Some(tool) = tool {
+            let icon = tool.icon();
+            let scale = (SLOT_SIZE - 16) as f32 / icon.width().max(icon.height());
+            atlas.draw_scaled(
+                d,
+                icon,
+                Vector2::new(
+                    x as f32 + (SLOT_SIZE as f32 - icon.width() * scale) / 2.0,
+                    y as f32 + (SLOT_SIZE as f32 - icon.height() * scale) / 2.0,
+                ),
+                scale,
+                Color::WHITE,
+            );
+            d.draw_text(
+                format!("x{}", tool.uses_left()).as_str(),
+                x + SLOT_SIZE - 22,
+                y + SLOT_SIZE - 16,
+                14,
+                Color::WHITE,
+            );
+        }
+    }
+}
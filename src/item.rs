@@ -1,18 +1,24 @@
-// basic item with position and borrowed texture
+// basic item with position and a sprite from the atlas
+use crate::texture_atlas::{SpriteRef, TextureAtlas};
+use crate::tiled_map::{MazeConfig, SCALE, TILE_HEIGHT, TILE_WIDTH};
 use crate::trait_collision::Collision;
 use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone)]
-pub struct Item<'a> {
+pub struct Item {
     pub position: Vector2,
-    pub texture: &'a Texture2D,
+    pub sprite: SpriteRef,
     width: f32,
     height: f32,
     scale: f32,
     pub item_type: ItemType,
+    pub uses: i32,
+    // Fallengelassene Items werden erst wieder aufgehoben, wenn der Spieler einmal runtergegangen ist
+    pub pickup_locked: bool,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ItemType {
     Axe,
     Pickaxe,
@@ -20,24 +26,103 @@ pub enum ItemType {
     Shovel,
 }
 
-impl<'a> Item<'a> {
-    pub fn new(position: Vector2, texture: &'a Texture2D, scale: f32, item_type: ItemType) -> Self {
+impl ItemType {
+    // Tile-ID des Hindernisses, das man mit dem Tool kaputt machen kann
+    pub fn destroys(&self) -> Option<i32> {
+        match self {
+            ItemType::Axe => Some(2),
+            ItemType::Pickaxe => Some(7),
+            ItemType::Shovel => Some(18),
+            ItemType::Gear => None,
+        }
+    }
+
+    pub fn texture_path(&self) -> &'static str {
+        match self {
+            ItemType::Axe => "assets/axe0.png",
+            ItemType::Pickaxe => "assets/pickaxe0.png",
+            ItemType::Gear => "assets/gear.png",
+            ItemType::Shovel => "assets/shovel0.png",
+        }
+    }
+}
+
+impl Item {
+    pub fn new(position: Vector2, sprite: SpriteRef, scale: f32, item_type: ItemType) -> Self {
         Item {
             position,
-            texture,
-            width: texture.width as f32 * scale,
-            height: texture.height as f32 * scale,
+            sprite,
+            width: sprite.width() * scale,
+            height: sprite.height() * scale,
             scale,
             item_type,
+            uses: 1,
+            pickup_locked: false,
+        }
+    }
+
+    // Ein fallengelassenes Tool, unten mittig an den Füßen des Spielers
+    pub fn dropped(feet: Vector2, sprite: SpriteRef, item_type: ItemType, uses: i32) -> Self {
+        let mut item = Item::new(feet, sprite, 1.0, item_type);
+        item.position.x -= item.width / 2.0;
+        item.position.y -= item.height;
+        item.uses = uses;
+        item.pickup_locked = true;
+        item
+    }
+
+    pub fn center(&self) -> Vector2 {
+        Vector2::new(
+            self.position.x + self.width / 2.0,
+            self.position.y + self.height / 2.0,
+        )
+    }
+
+    pub fn foot_y(&self) -> f32 {
+        self.position.y + self.height
+    }
+
+    pub fn render(&self, d: &mut RaylibMode2D<'_, RaylibDrawHandle>, atlas: &TextureAtlas) {
+        atlas.draw_scaled(d, self.sprite, self.position, self.scale, Color::WHITE);
+    }
+}
+
+// Alle Items, die in der Map-Datei stehen
+pub fn spawn_items(config: &MazeConfig, atlas: &TextureAtlas) -> Vec<Item> {
+    let tile_pos = |pos: &(i32, i32)| {
+        Vector2::new(
+            (pos.0 * TILE_WIDTH) as f32 * SCALE,
+            (pos.1 * TILE_HEIGHT) as f32 * SCALE,
+        )
+    };
+
+    let mut items: Vec<Item> = Vec::new();
+    let simple = [
+        (&config.pickaxes, ItemType::Pickaxe),
+        (&config.axes, ItemType::Axe),
+        (&config.shovels, ItemType::Shovel),
+        (&config.gears, ItemType::Gear),
+    ];
+    for (positions, item_type) in simple {
+        for pos in positions {
+            items.push(Item::new(tile_pos(pos), atlas.get_sprite(item_type.texture_path()), 1.0, item_type));
         }
     }
 
-    pub fn render(&self, d: &mut RaylibMode2D<'_, RaylibDrawHandle>) {
-        d.draw_texture_ex(self.texture, self.position, 0.0, self.scale, Color::WHITE);
+    for placed in config.items.iter() {
+        let mut item = Item::new(
+            tile_pos(&placed.pos),
+            atlas.get_sprite(placed.item.texture_path()),
+            1.0,
+            placed.item,
+        );
+        item.uses = placed.uses;
+        items.push(item);
     }
+    items
 }
 
-impl Collision for Item<'_> {
+impl Collision for Item {
     fn collision_with_rec(&self, other: &Rectangle) -> bool {
         Rectangle::new(
             self.position.x as f32,
@@ -49,7 +134,7 @@ impl Collision for Item<'_> {
     }
 }
 
-impl<'a> PartialEq for Item<'a> {
+impl PartialEq for Item {
     fn eq(&self, other: &Self) -> bool {
         std::ptr::eq(self, other)
     }
@@ -0,0 +1,106 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+
+use crate::hot_reload::asset_key;
+
+// Eine Datei pro Sprache, z.B. assets/lang/de.ron: { "hud.hp": "HP: {}", ... }
+pub const LANG_DIR: &str = "assets/lang";
+// Was hier drinsteht, gibt es immer. Fehlt in einer anderen Sprache ein Schlüssel, kommt er von hier.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+pub struct Localization {
+    pub language: String,
+    strings: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+    // Schon gemeldete fehlende Schlüssel, damit nicht jeden Frame geloggt wird
+    missing: RefCell<Vec<String>>,
+}
+
+impl Localization {
+    pub fn new(language: &str) -> Self {
+        let fallback = load_table(DEFAULT_LANGUAGE).unwrap_or_else(|why| {
+            println!("{}", why);
+            HashMap::new()
+        });
+        let strings = match language == DEFAULT_LANGUAGE {
+            true => HashMap::new(),
+            false => load_table(language).unwrap_or_else(|why| {
+                println!("{}, falling back to {}", why, DEFAULT_LANGUAGE);
+                HashMap::new()
+            }),
+        };
+        Localization {
+            language: language.to_string(),
+            strings,
+            fallback,
+            missing: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn reload(&mut self) {
+        *self = Localization::new(&self.language);
+        println!("HOT RELOAD: {} ({})", LANG_DIR, self.language);
+    }
+
+    // Gibt es den Schlüssel gar nicht, wird der Schlüssel selbst angezeigt. Fällt auf, stürzt aber nicht ab.
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        if let Some(text) = self.strings.get(key) {
+            return text;
+        }
+        if self.language != DEFAULT_LANGUAGE {
+            self.report_missing(key);
+        }
+        match self.fallback.get(key) {
+            Some(text) => text,
+            None => {
+                self.report_missing(key);
+                key
+            }
+        }
+    }
+
+    // Wie get, die "{}" im Text werden der Reihe nach durch args ersetzt
+    pub fn format(&self, key: &str, args: &[&dyn Display]) -> String {
+        let mut text = String::new();
+        let mut rest = self.get(key);
+        let mut args = args.iter();
+        while let Some(pos) = rest.find("{}") {
+            text.push_str(&rest[..pos]);
+            match args.next() {
+                Some(arg) => text.push_str(&arg.to_string()),
+                None => text.push_str("{}"),
+            }
+            rest = &rest[pos + 2..];
+        }
+        text.push_str(rest);
+        text
+    }
+
+    // Ordner mit Texten pro Sprache: assets/intro -> assets/intro/de, sonst assets/intro/en, sonst assets/intro
+    pub fn localized_dir(&self, dir: &str) -> String {
+        for language in [self.language.as_str(), DEFAULT_LANGUAGE] {
+            let localized = Path::new(dir).join(language);
+            if localized.is_dir() {
+                return asset_key(&localized);
+            }
+        }
+        dir.to_string()
+    }
+
+    fn report_missing(&self, key: &str) {
+        let mut missing = self.missing.borrow_mut();
+        if !missing.iter().any(|k| k == key) {
+            println!("Missing translation {} ({})", key, self.language);
+            missing.push(key.to_string());
+        }
+    }
+}
+
+fn load_table(language: &str) -> Result<HashMap<String, String>, String> {
+    let path = format!("{}/{}.ron", LANG_DIR, language);
+    let s = fs::read_to_string(&path).map_err(|why| format!("couldn't read {}: {}", path, why))?;
+    ron::from_str(s.as_str()).map_err(|why| format!("{}: {}", path, why))
+}
@@ -3,20 +3,37 @@ mod trait_collision;
 mod player;
 use std::{ops::Deref, vec};
 
-use player::{Animation, Player};
+use player::{HpTier, Player, PlayerStatus};
+
+mod animation;
 
 mod camera;
 use camera::GameCamera;
 
 use raylib::prelude::*;
 mod audiomanager;
-use audiomanager::AudioManager;
+use audiomanager::{AudioManager, Bus};
+
+mod audio_backend;
+use audio_backend::{AudioBackend, NullBackend, RaylibBackend};
+
+mod settings;
+use settings::{SETTINGS_PATH, Settings};
+
+mod localization;
+use localization::{LANG_DIR, Localization};
+
+mod hot_reload;
+use hot_reload::{AssetWatcher, asset_key};
+
+mod asset_manifest;
+use asset_manifest::AssetManifest;
 
 mod texture_atlas;
 use texture_atlas::TextureAtlas;
 
 mod tiled_map;
-use tiled_map::{MazeConfig, SCALE, TILE_HEIGHT, TILE_WIDTH, Tags, Tile, TiledMap};
+use tiled_map::{MazeConfig, SCALE, TILE_WIDTH, Tags, Tile, TiledMap};
 
 mod item;
 use item::Item;
@@ -28,251 +45,116 @@ use tool::Tool;
 mod intro;
 use intro::IntroSequence;
 
+mod markup;
+
+mod inventory;
+
+mod creature;
+use creature::Creature;
+
+mod pathfinding;
+use pathfinding::Pathfinder;
+
+mod hint;
+use hint::{HINT_COST, Hint};
+
+mod minimap;
+use minimap::Minimap;
+
+mod game_feel;
+use game_feel::{GameFeel, Material};
+
+mod render_queue;
+use render_queue::{RenderQueue, Renderable};
+
+mod particles;
+use particles::{Emitter, ParticleSystem, sand_puff, water_splash};
+
+mod objective;
+
+const MANIFEST_PATH: &str = "assets/manifest.ron";
+const MAZE_PATH: &str = "assets/maze2.KB";
+
 const WIDTH: i32 = 1280;
 const HEIGHT: i32 = 720;
 
 fn main() {
-    let (mut rl, thread) = raylib::init().size(WIDTH, HEIGHT).title("Hello, World").build();
+    // Zum Entwickeln: bei fehlenden Assets gar nicht erst starten
+    let strict_assets = std::env::args().any(|arg| arg == "--strict-assets");
+    // Ohne Soundkarte (CI, VMs) oder zum Testen: nichts abspielen, nur loggen
+    let no_audio = std::env::args().any(|arg| arg == "--no-audio");
+
+    let mut settings = Settings::load(SETTINGS_PATH);
+    // Alle Texte, die der Spieler sieht, kommen aus assets/lang/ und den Sprachordnern unter assets/intro usw.
+    let mut localization = Localization::new(&settings.language);
+
+    let (mut rl, thread) = raylib::init()
+        .size(WIDTH, HEIGHT)
+        .title(localization.get("window.title"))
+        .build();
 
     // AUDIO MANAGER
-    let mut audio_device =
-        RaylibAudio::init_audio_device().expect("Failed to initialize audio device");
-    unsafe {
-        ffi::SetAudioStreamBufferSizeDefault(4096);
-    }
-    let mut audio_manager: AudioManager = AudioManager::new(&mut audio_device);
+    // Klappt das Audio-Device nicht, läuft das Spiel einfach stumm weiter
+    let audio_device = match no_audio {
+        true => None,
+        false => match RaylibAudio::init_audio_device() {
+            Ok(device) => {
+                unsafe {
+                    ffi::SetAudioStreamBufferSizeDefault(4096);
+                }
+                Some(device)
+            }
+            Err(why) => {
+                println!("Failed to initialize audio device: {}, continuing without sound", why);
+                None
+            }
+        },
+    };
+    let audio_backend: Box<dyn AudioBackend + '_> = match &audio_device {
+        Some(device) => Box::new(RaylibBackend::new(device)),
+        None => Box::new(NullBackend::new()),
+    };
+    let mut audio_manager: AudioManager = AudioManager::new(audio_backend, settings.audio.clone());
 
-    audio_manager.load_sound("hit_stone", "assets/sounds/stone.ogg");
-    audio_manager.load_sound("hit_wood", "assets/sounds/wood.wav");
-    audio_manager.load_sound("hit_sand", "assets/sounds/sand.ogg");
-    audio_manager.load_sound("step_sand_1", "assets/sounds/sand_step_1.wav");
-    audio_manager.load_sound("step_sand_2", "assets/sounds/sand_step_2.wav");
-    audio_manager.load_sound("ui", "assets/sounds/menu.wav");
+    let manifest = match AssetManifest::new(MANIFEST_PATH) {
+        Ok(manifest) => manifest,
+        Err(why) => panic!("{}", why),
+    };
+    audio_manager.load_manifest(&manifest);
+    // Wie die Schritte auf welchem Boden klingen, auch aus dem Manifest
+    let mut footsteps = manifest.footsteps.clone();
 
-    let test: MazeConfig = match MazeConfig::new("assets/maze2.KB") {
+    let mut test: MazeConfig = match MazeConfig::new(MAZE_PATH) {
         Ok(config) => config,
         Err(why) => panic!("{}", why),
     };
-    // Hier alle Texturen einfügen, die automatisch geladen werden sollen
-    // Sie können dann später mit atlas.get_texture("pfad/zu/texture") abgerufen werden
-    let textures = [
-        "assets/run0.png",
-        "assets/run1.png",
-        "assets/run2.png",
-        "assets/run3.png",
-        "assets/run4.png",
-        "assets/run5.png",
-        "assets/run6.png",
-        "assets/run0_80.png",
-        "assets/run1_80.png",
-        "assets/run2_80.png",
-        "assets/run3_80.png",
-        "assets/run4_80.png",
-        "assets/run5_80.png",
-        "assets/run0_60.png",
-        "assets/run1_60.png",
-        "assets/run2_60.png",
-        "assets/run3_60.png",
-        "assets/run4_60.png",
-        "assets/run5_60.png",
-        "assets/run0_40.png",
-        "assets/run1_40.png",
-        "assets/run2_40.png",
-        "assets/run3_40.png",
-        "assets/run4_40.png",
-        "assets/run5_40.png",
-        "assets/run0_20.png",
-        "assets/run1_20.png",
-        "assets/run2_20.png",
-        "assets/run3_20.png",
-        "assets/run4_20.png",
-        "assets/run5_20.png",
-        "assets/idle0.png",
-        "assets/idle1.png",
-        "assets/idle2.png",
-        "assets/idle3.png",
-        "assets/idle4.png",
-        "assets/idle5.png",
-        "assets/idle6.png",
-        "assets/idle0_80.png",
-        "assets/idle1_80.png",
-        "assets/idle2_80.png",
-        "assets/idle3_80.png",
-        "assets/idle0_60.png",
-        "assets/idle1_60.png",
-        "assets/idle2_60.png",
-        "assets/idle3_60.png",
-        "assets/idle0_40.png",
-        "assets/idle1_40.png",
-        "assets/idle2_40.png",
-        "assets/idle3_40.png",
-        "assets/idle0_20.png",
-        "assets/idle1_20.png",
-        "assets/idle2_20.png",
-        "assets/idle3_20.png",
-        "assets/palme0.png",
-        "assets/palme1.png",
-        "assets/palme2.png",
-        "assets/palme3.png",
-        "assets/stein0.png",
-        "assets/stein1.png",
-        "assets/stein2.png",
-        "assets/stein3.png",
-        "assets/stein4.png",
-        "assets/empty_tile.png",
-        "assets/sand_tile.png",
-        "assets/potion.png",
-        "assets/water0.png",
-        "assets/water1.png",
-        "assets/water2.png",
-        "assets/water3.png",
-        "assets/Sandmauer.png",
-        "assets/axe0.png",
-        "assets/axe1.png",
-        "assets/axe2.png",
-        "assets/hammer0.png",
-        "assets/hammer1.png",
-        "assets/hammer2.png",
-        "assets/pickaxe0.png",
-        "assets/pickaxe1.png",
-        "assets/pickaxe2.png",
-        "assets/shovel0.png",
-        "assets/shovel1.png",
-        "assets/shovel2.png",
-        "assets/shovel3.png",
-        "assets/gear.png",
-        "assets/pile0.png",
-        "assets/pile1.png",
-        "assets/pile2.png",
-        "assets/pile3.png",
-        "assets/pile4.png",
-        "assets/pile5.png",
-        "assets/pfutze.png",
-    ];
-
+    // Texturen, Animationen und Sounds stehen in assets/manifest.ron
     let mut atlas = TextureAtlas::new();
-    for path in textures.iter() {
-        let texture = rl.load_texture(&thread, path).unwrap();
-        atlas.store_texture(path, texture);
-    }
+    atlas.load_manifest(&manifest);
+    atlas.pack(&mut rl, &thread).unwrap();
 
     // PLAYER
-    let run_frames0 = vec![
-        atlas.get_texture("assets/run0_20.png"),
-        atlas.get_texture("assets/run1_20.png"),
-        atlas.get_texture("assets/run2_20.png"),
-        atlas.get_texture("assets/run3_20.png"),
-        atlas.get_texture("assets/run4_20.png"),
-        atlas.get_texture("assets/run5_20.png"),
-    ];
-
-    let run_frames1 = vec![
-        atlas.get_texture("assets/run0_20.png"),
-        atlas.get_texture("assets/run1_20.png"),
-        atlas.get_texture("assets/run2_20.png"),
-        atlas.get_texture("assets/run3_20.png"),
-        atlas.get_texture("assets/run4_20.png"),
-        atlas.get_texture("assets/run5_20.png"),
-    ];
-    
-    let run_frames2 = vec![
-        atlas.get_texture("assets/run0_40.png"),
-        atlas.get_texture("assets/run1_40.png"),
-        atlas.get_texture("assets/run2_40.png"),
-        atlas.get_texture("assets/run3_40.png"),
-        atlas.get_texture("assets/run4_40.png"),
-        atlas.get_texture("assets/run5_40.png"),
-    ];
-    
-    let run_frames3 = vec![
-        atlas.get_texture("assets/run0_60.png"),
-        atlas.get_texture("assets/run1_60.png"),
-        atlas.get_texture("assets/run2_60.png"),
-        atlas.get_texture("assets/run3_60.png"),
-        atlas.get_texture("assets/run4_60.png"),
-        atlas.get_texture("assets/run5_60.png"),
-    ];
-    
-    let run_frames4 = vec![
-        atlas.get_texture("assets/run0_80.png"),
-        atlas.get_texture("assets/run1_80.png"),
-        atlas.get_texture("assets/run2_80.png"),
-        atlas.get_texture("assets/run3_80.png"),
-        atlas.get_texture("assets/run4_80.png"),
-        atlas.get_texture("assets/run5_80.png"),
-    ];
-
-    let idle_frames0 = vec![
-        atlas.get_texture("assets/idle0_20.png"),
-        atlas.get_texture("assets/idle1_20.png"),
-        atlas.get_texture("assets/idle2_20.png"),
-        atlas.get_texture("assets/idle3_20.png"),
-    ];
-
-    let idle_frames1 = vec![
-        atlas.get_texture("assets/idle0_20.png"),
-        atlas.get_texture("assets/idle1_20.png"),
-        atlas.get_texture("assets/idle2_20.png"),
-        atlas.get_texture("assets/idle3_20.png"),
-    ];
-
-    let idle_frames2 = vec![
-        atlas.get_texture("assets/idle0_40.png"),
-        atlas.get_texture("assets/idle1_40.png"),
-        atlas.get_texture("assets/idle2_40.png"),
-        atlas.get_texture("assets/idle3_40.png"),
-    ];
-
-    let idle_frames3 = vec![
-        atlas.get_texture("assets/idle0_60.png"),
-        atlas.get_texture("assets/idle1_60.png"),
-        atlas.get_texture("assets/idle2_60.png"),
-        atlas.get_texture("assets/idle3_60.png"),
-    ];
-
-    let idle_frames4 = vec![
-        atlas.get_texture("assets/idle0_80.png"),
-        atlas.get_texture("assets/idle1_80.png"),
-        atlas.get_texture("assets/idle2_80.png"),
-        atlas.get_texture("assets/idle3_80.png"),
-    ];
-
-    let axe_frames = vec![
-        atlas.get_texture("assets/axe0.png"),
-        atlas.get_texture("assets/axe1.png"),
-        atlas.get_texture("assets/axe2.png"),
-    ];
-    let pickaxe_frames = vec![
-        atlas.get_texture("assets/pickaxe0.png"),
-        atlas.get_texture("assets/pickaxe1.png"),
-        atlas.get_texture("assets/pickaxe2.png"),
-    ];
-    let shovel_frames = vec![
-        atlas.get_texture("assets/shovel0.png"),
-        atlas.get_texture("assets/shovel1.png"),
-        atlas.get_texture("assets/shovel2.png"),
-        atlas.get_texture("assets/shovel3.png"),
-    ];
+    // HP-Stufen kommen aus player_tiers im Manifest, beliebig viele
+    if manifest.player_tiers.is_empty() {
+        panic!("{}: player_tiers is empty", MANIFEST_PATH);
+    }
+    let tiers: Vec<HpTier> = manifest
+        .player_tiers
+        .iter()
+        .map(|config| HpTier::new(config, &atlas))
+        .collect();
+
+    let axe_frames = atlas.get_animation("axe");
+    let pickaxe_frames = atlas.get_animation("pickaxe");
+    let shovel_frames = atlas.get_animation("shovel");
 
     let mut player = Player::new(
-        Vector2::new(
-            ((test.player.0 * TILE_WIDTH) as f32 + TILE_WIDTH as f32 / 2.0) * SCALE,
-            ((test.player.1 * TILE_HEIGHT) as f32 + TILE_HEIGHT as f32 / 2.0) * SCALE,
-        ),
-        (
-            &idle_frames0,
-            &idle_frames1,
-            &idle_frames2,
-            &idle_frames3,
-            &idle_frames4,
-        ),
-        (
-            &run_frames0,
-            &run_frames1,
-            &run_frames2,
-            &run_frames3,
-            &run_frames4,
-        ),
+        test.player_spawn(),
+        tiers,
+        test.max_hp,
+        &atlas.get_animation("player_swing"),
+        &atlas.get_animation("player_hurt"),
+        &atlas.get_animation("player_death"),
     );
 
     // CAMERA
@@ -285,8 +167,6 @@ fn main() {
         },
     );
 
-    let mut frame_times = 0 as f32;
-
     // TILED MAP
     // let mut tiled_map: TiledMap<'_> = TiledMap::new(5, 20, 20, &atlas);
     let mut tiled_map = match TiledMap::from(&test, &atlas) {
@@ -295,137 +175,299 @@ fn main() {
     };
     let mut background_tiled_map = TiledMap::water(1, 50, 50, &atlas);
 
-    // ITEMS
-    let mut items: Vec<Item> = Vec::new();
-
-    for pickaxe in test.pickaxes {
-        items.push(Item::new(
-            Vector2::new(
-                (pickaxe.0 * TILE_WIDTH) as f32 * SCALE,
-                (pickaxe.1 * TILE_HEIGHT) as f32 * SCALE,
-            ),
-            atlas.get_texture("assets/pickaxe0.png"),
-            1.0,
-            item::ItemType::Pickaxe,
-        ));
-    }
+    let mut minimap = Minimap::new(&tiled_map);
 
-    for pickaxe in test.axes {
-        items.push(Item::new(
-            Vector2::new(
-                (pickaxe.0 * TILE_WIDTH) as f32 * SCALE,
-                (pickaxe.1 * TILE_HEIGHT) as f32 * SCALE,
-            ),
-            atlas.get_texture("assets/axe0.png"),
-            1.0,
-            item::ItemType::Axe,
-        ));
-    }
+    game_camera.set_bounds(tiled_map.bounds());
 
-    for pickaxe in test.shovels {
-        items.push(Item::new(
-            Vector2::new(
-                (pickaxe.0 * TILE_WIDTH) as f32 * SCALE,
-                (pickaxe.1 * TILE_HEIGHT) as f32 * SCALE,
-            ),
-            atlas.get_texture("assets/shovel0.png"),
-            1.0,
-            item::ItemType::Shovel,
-        ));
-    }
+    // CREATURES
+    let mut pathfinder = Pathfinder::new();
+    let mut creatures: Vec<Creature> = test
+        .creatures
+        .iter()
+        .map(|config| Creature::new(config, &tiled_map))
+        .collect();
 
-    for pickaxe in test.gears {
-        items.push(Item::new(
-            Vector2::new(
-                (pickaxe.0 * TILE_WIDTH) as f32 * SCALE,
-                (pickaxe.1 * TILE_HEIGHT) as f32 * SCALE,
-            ),
-            atlas.get_texture("assets/gear.png"),
-            1.0,
-            item::ItemType::Gear,
-        ));
+    // ITEMS
+    let mut items: Vec<Item> = item::spawn_items(&test, &atlas);
+
+    // Erst hier, damit auch Tiles und Items mitgezählt werden
+    let missing: Vec<String> = atlas
+        .missing()
+        .into_iter()
+        .chain(audio_manager.missing().iter().cloned())
+        .collect();
+    if let Err(why) = asset_manifest::report_missing(&missing, strict_assets) {
+        panic!("{}", why);
     }
 
-    let intro = match IntroSequence::new("assets/intro") {
+    // Im Debug-Build werden geänderte Dateien unter assets/ sofort neu geladen
+    let mut watcher = AssetWatcher::new("assets", cfg!(debug_assertions));
+
+    let intro_dir = localization.localized_dir("assets/intro");
+    let mut intro = match IntroSequence::new(&intro_dir) {
         Ok(intro) => intro,
         Err(err) => {
             println!("Failed to load intro sequence: {}", err);
-            IntroSequence {
-                files_content: Vec::new(),
-            }
+            IntroSequence::empty(&intro_dir)
         }
     };
+    intro.chars_per_second = settings.text_speed;
 
     // INTRO, WIEDER EINKOMMENTIEREN!
-    if !intro.play(&mut rl, &thread, &mut audio_manager) {
+    if !intro.play(&mut rl, &thread, &mut audio_manager, &mut watcher, &localization, "intro") {
         return; // Exit if window was closed during intro
     }
 
     let mut elapsed_time = 0.0;
 
     rl.set_target_fps(120);
-    let mut walk_sound_counter = 0.0;
-    let mut walk_sound_switch = false;
+    let mut goal_message_timer = 0.0;
+    let mut hint = Hint::new();
+    let mut game_feel = GameFeel::new();
+    let mut particles = ParticleSystem::new();
+    let mut render_queue = RenderQueue::new();
+    for shore in tiled_map.shoreline() {
+        particles.add_emitter(Emitter::new(
+            tiled_map.tile_center(shore),
+            water_splash(),
+        ));
+    }
+    audio_manager.play_scene_music("gameplay");
     while !rl.window_should_close() {
-        let delta_time = rl.get_frame_time();
+        let delta_time = game_feel.update(rl.get_frame_time());
+        audio_manager.update(rl.get_frame_time());
+
+        // HOT RELOAD
+        watcher.update(rl.get_frame_time());
+        for path in watcher.take(|_| true) {
+            let key = asset_key(&path);
+            match path.extension().and_then(|ext| ext.to_str()) {
+                Some("png") => {
+                    if let Err(why) = atlas.reload_texture(&mut rl, &thread, &key) {
+                        println!("{}", why);
+                    }
+                }
+                Some("wav") | Some("ogg") => audio_manager.reload_path(&key),
+                // Neue Texturen und Sounds kommen dazu, schon gebaute Animationen bleiben wie sie sind
+                Some("ron") if key == MANIFEST_PATH => match AssetManifest::new(MANIFEST_PATH) {
+                    Ok(manifest) => {
+                        audio_manager.load_manifest(&manifest);
+                        atlas.load_manifest(&manifest);
+                        footsteps = manifest.footsteps;
+                        if let Err(why) = atlas.pack(&mut rl, &thread) {
+                            println!("{}", why);
+                        }
+                    }
+                    Err(why) => println!("Failed to reload manifest: {}", why),
+                },
+                Some("ron") if key.starts_with(LANG_DIR) => localization.reload(),
+                // Map neu bauen. Items und Krabben fangen von vorne an, der Spieler bleibt stehen, wenn er darf.
+                // Die Teile liegen wieder auf der Map, also muss er sie auch neu einsammeln.
+                Some("KB") if key == MAZE_PATH => {
+                    let reloaded = MazeConfig::new(MAZE_PATH)
+                        .and_then(|config| TiledMap::from(&config, &atlas).map(|map| (config, map)));
+                    match reloaded {
+                        Ok((config, map)) => {
+                            println!("HOT RELOAD: {}", key);
+                            test = config;
+                            tiled_map = map;
+                            minimap = Minimap::new(&tiled_map);
+                            game_camera.set_bounds(tiled_map.bounds());
+                            pathfinder = Pathfinder::new();
+                            creatures = test
+                                .creatures
+                                .iter()
+                                .map(|config| Creature::new(config, &tiled_map))
+                                .collect();
+                            items = item::spawn_items(&test, &atlas);
+                            player.parts.clear();
+                            player.max_hp = test.max_hp;
+                            player.hp = player.hp.min(test.max_hp);
+
+                            particles.clear_emitters();
+                            for shore in tiled_map.shoreline() {
+                                particles.add_emitter(Emitter::new(
+                                    tiled_map.tile_center(shore),
+                                    water_splash(),
+                                ));
+                            }
+
+                            let feet = tiled_map.tile_at(player.feet());
+                            if tiled_map.is_tile_blocked(feet.0, feet.1)
+                                || tiled_map.is_blocked(&player.get_collision_rect())
+                            {
+                                player.pos = test.player_spawn();
+                            }
+                        }
+                        Err(why) => println!("Failed to reload {}: {}", key, why),
+                    }
+                }
+                _ => (),
+            }
+        }
         elapsed_time += delta_time;
-        walk_sound_counter += delta_time;
 
         player.movement.reset();
-        let mut walking = false;
         if rl.is_key_down(KeyboardKey::KEY_W) {
             player.up();
-            walking = true;
         }
 
         if rl.is_key_down(KeyboardKey::KEY_S) {
             player.down();
-            walking = true;
         }
 
         if rl.is_key_down(KeyboardKey::KEY_D) {
             player.right();
-            walking = true;
         }
 
         if rl.is_key_down(KeyboardKey::KEY_A) {
             player.left();
-            walking = true;
         }
-        if walking && walk_sound_counter > 0.25 {
-            walk_sound_counter = 0.0;
-            walk_sound_switch = !walk_sound_switch;
-            match walk_sound_switch {
-                true => audio_manager.play_sound("step_sand_1"),
-                false => audio_manager.play_sound("step_sand_2"),
+        if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
+            player.use_tool();
+        }
+        // LAUTSTÄRKE: N schaltet alles stumm, F5/F6 Musik leiser/lauter, F7/F8 Effekte
+        let volume_keys = [
+            (KeyboardKey::KEY_F5, Bus::Music, -0.1),
+            (KeyboardKey::KEY_F6, Bus::Music, 0.1),
+            (KeyboardKey::KEY_F7, Bus::Sfx, -0.1),
+            (KeyboardKey::KEY_F8, Bus::Sfx, 0.1),
+        ];
+        let mut settings_changed = false;
+        if rl.is_key_pressed(KeyboardKey::KEY_N) {
+            audio_manager.toggle_mute(Bus::Master);
+            settings_changed = true;
+        }
+        for (key, bus, delta) in volume_keys {
+            if rl.is_key_pressed(key) {
+                audio_manager.change_volume(bus, delta);
+                settings_changed = true;
             }
         }
-        if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
+        if settings_changed {
+            settings.audio = audio_manager.settings().clone();
+            if let Err(why) = settings.save(SETTINGS_PATH) {
+                println!("{}", why);
+            }
+        }
+        // Getroffen wird erst, wenn die Schwung-Animation beim "hit"-Frame ist
+        for hand in player.animation_update(delta_time) {
             let marked_tiles: Vec<(Tile, Vector2)> =
-                player.use_tool(&tiled_map, &mut audio_manager);
+                player.tool_hit(&hand, &tiled_map, &mut audio_manager);
+            for (tile, pos) in marked_tiles.iter() {
+                if let Some(material) = Material::of(tile) {
+                    let half_tile = TILE_WIDTH as f32 * SCALE / 2.0;
+                    game_feel.impact(
+                        material,
+                        *pos + Vector2::new(half_tile, half_tile),
+                        tiled_map.sprite_of(tile),
+                        &mut particles,
+                    );
+                }
+            }
             tiled_map.handle_hit_tiles(marked_tiles);
         }
         if rl.is_key_pressed(KeyboardKey::KEY_F) {
             player.switch_tools();
         }
+        if rl.is_key_pressed(KeyboardKey::KEY_Q) {
+            player.swap_with_backpack();
+        }
+        let slot_keys = [
+            KeyboardKey::KEY_ONE,
+            KeyboardKey::KEY_TWO,
+            KeyboardKey::KEY_THREE,
+            KeyboardKey::KEY_FOUR,
+        ];
+        for (slot, key) in slot_keys.iter().enumerate() {
+            if rl.is_key_pressed(*key) {
+                player.select_backpack_slot(slot);
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_H) && player.hp > HINT_COST {
+            // Bezahlt wird nur, wenn es auch was zu sehen gibt
+            if let Some(path) = hint::plan_route(
+                &tiled_map,
+                &mut pathfinder,
+                tiled_map.tile_at(player.feet()),
+                &player.tool_uses(),
+                &items,
+                !test.objective.is_met(&player.parts),
+            ) {
+                player.hp -= HINT_COST;
+                audio_manager.play_ui_sound("ui");
+                hint.show(path);
+            }
+        }
+        hint.update(delta_time);
+        particles.update(delta_time);
+        if rl.is_key_pressed(KeyboardKey::KEY_M) {
+            minimap.toggle();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_EQUAL) {
+            minimap.zoom(2.0);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_MINUS) {
+            minimap.zoom(-2.0);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_G) {
+            if let Some(tool) = player.drop_tool() {
+                items.push(Item::dropped(
+                    player.feet(),
+                    atlas.get_sprite(tool.item_type().texture_path()),
+                    tool.item_type(),
+                    tool.uses_left(),
+                ));
+            }
+        }
 
-        let finish = player.update(delta_time, &tiled_map);
+        goal_message_timer -= delta_time;
+        match player.update(delta_time, &tiled_map) {
+            PlayerStatus::Dead => break,
+            PlayerStatus::AtGoal if test.objective.is_met(&player.parts) => break,
+            PlayerStatus::AtGoal => {
+                if goal_message_timer <= 0.0 {
+                    audio_manager.play_ui_sound("ui");
+                }
+                goal_message_timer = 2.0;
+            }
+            PlayerStatus::Alive => (),
+        }
+        // Schritte kommen vom Spieler, je nach zurückgelegter Strecke und Boden
+        if player.take_footstep() {
+            let feet = player.feet();
+            if let Some(step) = tiled_map.footstep_at(tiled_map.tile_at(feet), &footsteps) {
+                if step.dust {
+                    particles.burst(&sand_puff(), feet);
+                }
+                audio_manager.play_sound_at(&step.sound, feet);
+            }
+        }
 
-        if finish {
-            break;
+        for creature in creatures.iter_mut() {
+            creature.update(delta_time, &tiled_map, &mut pathfinder, player.feet());
+            player.hurt(creature.attack(&player.get_collision_rect()));
         }
+        minimap.explore(tiled_map.tile_at(player.feet()));
 
         // Item collisions
         let player_dings = player.get_collision_rect();
 
+        for item in items.iter_mut() {
+            if item.pickup_locked && !item.collision_with_rec(&player_dings) {
+                item.pickup_locked = false;
+            }
+        }
+
         let collided_indices: Vec<usize> = items
             .iter()
             .enumerate()
-            .filter(|(_, i)| i.collision_with_rec(&player_dings))
+            .filter(|(_, i)| !i.pickup_locked && i.collision_with_rec(&player_dings))
             .map(|(index, _)| index)
             .collect();
 
-        for &index in collided_indices.iter() {
+        // Rückwärts, sonst verschieben sich die Indizes beim Entfernen
+        for &index in collided_indices.iter().rev() {
             if player.add_tool(
                 &items[index],
                 &atlas,
@@ -438,71 +480,125 @@ fn main() {
         }
 
         // Update camera target to follow player
-        game_camera.update_target(player.pos, 20.0, 20.0);
+        let wheel = rl.get_mouse_wheel_move();
+        if wheel != 0.0 {
+            game_camera.zoom(wheel);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_Z) {
+            game_camera.zoom(1.0);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_X) {
+            game_camera.zoom(-1.0);
+        }
+        game_camera.update_target(player.pos, 20.0, 20.0, delta_time);
+        audio_manager.set_listener(game_camera.camera.target, game_camera.half_view());
 
         {
             let mut dh = rl.begin_drawing(&thread);
 
             dh.clear_background(Color::WHITE);
 
-            let mut d = dh.begin_mode2D(game_camera.camera);
-            background_tiled_map.update_animated_tiles(delta_time);
-            background_tiled_map.render(&mut d);
-            tiled_map.update_animated_tiles(delta_time);
-            tiled_map.render(&mut d);
+            {
+                let mut d = dh.begin_mode2D(game_camera.view(&game_feel));
+                background_tiled_map.update_animated_tiles(delta_time);
+                background_tiled_map.render(&mut d, &atlas);
+                tiled_map.update_animated_tiles(delta_time);
+                tiled_map.render_layer(&mut d, &atlas, 0);
+
+                // Objekte, Items und alles was läuft nach Füßen sortiert
+                for layer in 1..tiled_map.layers {
+                    for x in 0..tiled_map.size_x {
+                        for y in 0..tiled_map.size_y {
+                            render_queue.push(tiled_map.tile_foot_y(y), Renderable::Tile { layer, x, y });
+                        }
+                    }
+                }
+                for (index, item) in items.iter().enumerate() {
+                    render_queue.push(item.foot_y(), Renderable::Item(index));
+                }
+                for (index, creature) in creatures.iter().enumerate() {
+                    render_queue.push(creature.pos.y, Renderable::Creature(index));
+                }
+                render_queue.push(player.feet().y, Renderable::Player);
+
+                for renderable in render_queue.drain_sorted() {
+                    match renderable {
+                        Renderable::Tile { layer, x, y } => tiled_map.render_tile(&mut d, &atlas, layer, x, y),
+                        Renderable::Item(index) => items[index].render(&mut d, &atlas),
+                        Renderable::Creature(index) => creatures[index].render(&mut d),
+                        Renderable::Player => player.draw(&mut d, &atlas, delta_time, elapsed_time),
+                    }
+                }
 
-            for item in items.iter() {
-                item.render(&mut d);
+                hint.render(&mut d, &tiled_map);
+                particles.render(&mut d, &atlas);
+
+                d.draw_fps(12, 12);
+                d.draw_text(
+                    localization.format("hud.hp", &[&player.hp]).as_str(),
+                    (player.pos.x - 100.0) as i32,
+                    (player.pos.y + 50.0) as i32,
+                    30,
+                    Color::RED,
+                );
+                if test.objective.required() > 0 {
+                    d.draw_text(
+                        localization
+                            .format(
+                                "hud.parts",
+                                &[&test.objective.collected(&player.parts), &test.objective.required()],
+                            )
+                            .as_str(),
+                        (player.pos.x - 100.0) as i32,
+                        (player.pos.y + 80.0) as i32,
+                        30,
+                        Color::DARKGRAY,
+                    );
+                }
+                if goal_message_timer > 0.0 {
+                    d.draw_text(
+                        localization
+                            .format("hud.missing_parts", &[&test.objective.missing(&player.parts)])
+                            .as_str(),
+                        (player.pos.x - 200.0) as i32,
+                        (player.pos.y - 40.0) as i32,
+                        30,
+                        Color::ORANGE,
+                    );
+                }
             }
 
-            d.draw_fps(12, 12);
-            d.draw_text(
-                format!("HP: {}", player.hp).as_str(),
-                (player.pos.x - 100.0) as i32,
-                (player.pos.y + 50.0) as i32,
-                30,
-                Color::RED,
-            );
-            d.clear_background(Color::WHITE);
-            d.draw_fps(12, 12);
-
-            player.draw(&mut d, delta_time, elapsed_time);
-        }
-
-        if frame_times > 0.12 {
-            player.animation_update();
-            frame_times = 0 as f32;
-        } else {
-            frame_times += rl.get_frame_time()
+            player.draw_hud(&mut dh, &atlas);
+            minimap.draw(&mut dh, &tiled_map, player.feet(), &items);
         }
     }
 
-    if player.hp > 0 {
+    if player.hp > 0 && test.objective.is_met(&player.parts) {
         // Endscreen
-        let outro = match IntroSequence::new("assets/outro") {
+        let outro_dir = localization.localized_dir("assets/outro");
+        let mut outro = match IntroSequence::new(&outro_dir) {
             Ok(outro) => outro,
             Err(err) => {
                 println!("Failed to load outro sequence: {}", err);
-                IntroSequence {
-                    files_content: Vec::new(),
-                }
+                IntroSequence::empty(&outro_dir)
             }
         };
-        if !outro.play(&mut rl, &thread, &mut audio_manager) {
+        outro.chars_per_second = settings.text_speed;
+        if !outro.play(&mut rl, &thread, &mut audio_manager, &mut watcher, &localization, "outro") {
             return; // Exit if window was closed during outro
         }
     } else {
         // Endscreen
-        let outro = match IntroSequence::new("assets/outro_bad") {
+        let outro_dir = localization.localized_dir("assets/outro_bad");
+        let mut outro = match IntroSequence::new(&outro_dir) {
             Ok(outro) => outro,
             Err(err) => {
                 println!("Failed to load outro sequence: {}", err);
-                IntroSequence {
-                    files_content: Vec::new(),
-                }
+                IntroSequence::empty(&outro_dir)
             }
         };
-        if !outro.play(&mut rl, &thread, &mut audio_manager) {
+        outro.chars_per_second = settings.text_speed;
+        if !outro.play(&mut rl, &thread, &mut audio_manager, &mut watcher, &localization, "outro_bad") {
             return; // Exit if window was closed during outro
         }
     }
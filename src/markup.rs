@@ -0,0 +1,315 @@
+use raylib::prelude::*;
+
+// Kleines Markup für Intro- und Outro-Seiten:
+//   Leerzeile              neuer Absatz
+//   \n                     Zeilenumbruch (alte Schreibweise, geht weiterhin)
+//   *wichtig*              hervorgehoben
+//   [color red]...[/color] farbig: red, green, blue, yellow, orange, gold, gray, white oder #rrggbb
+//   [pause 1.5]            der Typewriter hält 1.5 s an
+//   [speed 0.5]            ab hier halb so schnell tippen (relativ zur eingestellten Geschwindigkeit)
+//   [sound name]           Sound aus dem Manifest, sobald der Typewriter hier ist
+//   [music name]           zu diesem Musikstück überblenden, sobald der Typewriter hier ist
+//   [auto 4]               4 s nachdem die Seite fertig ist, geht es von selbst weiter
+//   [image assets/x.png]   Bild über dem Text
+// \* und \[ stehen für das Zeichen selbst.
+
+const TEXT_COLOR: Color = Color::WHITE;
+const EMPHASIS_COLOR: Color = Color::GOLD;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+    // joined: hängt ohne Leerzeichen am vorigen Wort, z.B. das "!" in "*Rust*!"
+    Word { text: String, color: Color, joined: bool },
+    LineBreak,
+    Paragraph,
+    Pause(f32),
+    Speed(f32),
+    Cue(Cue),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Cue {
+    Sound(String),
+    Music(String),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Page {
+    pub tokens: Vec<Token>,
+    pub image: Option<String>,
+    pub auto_advance: Option<f32>,
+}
+
+struct Parser {
+    page: Page,
+    word: String,
+    joined: bool,
+    emphasis: bool,
+    colors: Vec<Color>,
+}
+
+impl Parser {
+    fn color(&self) -> Color {
+        if self.emphasis {
+            return EMPHASIS_COLOR;
+        }
+        self.colors.last().copied().unwrap_or(TEXT_COLOR)
+    }
+
+    // Schließt das aktuelle Wortstück ab. Was direkt danach kommt, hängt dran.
+    fn flush(&mut self) {
+        if self.word.is_empty() {
+            return;
+        }
+        let text = std::mem::take(&mut self.word);
+        self.page.tokens.push(Token::Word {
+            text,
+            color: self.color(),
+            joined: self.joined,
+        });
+        self.joined = true;
+    }
+
+    fn space(&mut self) {
+        self.flush();
+        self.joined = false;
+    }
+
+    fn push(&mut self, token: Token) {
+        self.space();
+        // Mehrere Absätze hintereinander oder ein Absatz ganz am Anfang bringen nichts
+        if token == Token::Paragraph
+            && matches!(self.page.tokens.last(), None | Some(Token::Paragraph))
+        {
+            return;
+        }
+        self.page.tokens.push(token);
+    }
+
+    // Gibt false zurück, wenn das kein bekannter Tag ist, dann bleibt er als Text stehen
+    fn tag(&mut self, tag: &str) -> bool {
+        let (name, arg) = tag.split_once(' ').unwrap_or((tag, ""));
+        let arg = arg.trim();
+        match name {
+            "pause" => match arg.parse::<f32>() {
+                Ok(seconds) => self.push(Token::Pause(seconds)),
+                Err(_) => return false,
+            },
+            "speed" => match arg.parse::<f32>() {
+                Ok(factor) if factor > 0.0 => self.push(Token::Speed(factor)),
+                _ => return false,
+            },
+            "auto" => match arg.parse::<f32>() {
+                Ok(seconds) => {
+                    self.space();
+                    self.page.auto_advance = Some(seconds);
+                }
+                Err(_) => return false,
+            },
+            "sound" if !arg.is_empty() => self.push(Token::Cue(Cue::Sound(arg.to_string()))),
+            "music" if !arg.is_empty() => self.push(Token::Cue(Cue::Music(arg.to_string()))),
+            "image" if !arg.is_empty() => {
+                self.space();
+                self.page.image = Some(arg.to_string());
+            }
+            "color" => match color_by_name(arg) {
+                Some(color) => {
+                    self.flush();
+                    self.colors.push(color);
+                }
+                None => return false,
+            },
+            "/color" => {
+                self.flush();
+                self.colors.pop();
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+fn color_by_name(name: &str) -> Option<Color> {
+    match name {
+        "red" => Some(Color::RED),
+        "green" => Some(Color::GREEN),
+        "blue" => Some(Color::SKYBLUE),
+        "yellow" => Some(Color::YELLOW),
+        "orange" => Some(Color::ORANGE),
+        "gold" => Some(Color::GOLD),
+        "gray" | "grey" => Some(Color::GRAY),
+        "white" => Some(Color::WHITE),
+        hex if hex.starts_with('#') => Color::from_hex(&hex[1..]).ok(),
+        _ => None,
+    }
+}
+
+pub fn parse(source: &str) -> Page {
+    let mut parser = Parser {
+        page: Page::default(),
+        word: String::new(),
+        joined: false,
+        emphasis: false,
+        colors: Vec::new(),
+    };
+
+    let chars: Vec<char> = source.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\\' if chars.get(i + 1) == Some(&'n') => {
+                parser.push(Token::LineBreak);
+                i += 2;
+                continue;
+            }
+            '\\' if matches!(chars.get(i + 1), Some('*') | Some('[')) => {
+                parser.word.push(chars[i + 1]);
+                i += 2;
+                continue;
+            }
+            '*' => {
+                parser.flush();
+                parser.emphasis = !parser.emphasis;
+            }
+            '[' => {
+                let close = chars[i..].iter().position(|c| *c == ']').map(|offset| i + offset);
+                let tag: Option<String> = close.map(|close| chars[i + 1..close].iter().collect());
+                match (close, tag) {
+                    (Some(close), Some(tag)) if parser.tag(tag.trim()) => i = close,
+                    _ => parser.word.push('['),
+                }
+            }
+            c if c.is_whitespace() => {
+                // Ganzen Leerraum auf einmal: mit Leerzeile drin ist es ein neuer Absatz
+                let mut newlines = 0;
+                while i < chars.len() && chars[i].is_whitespace() {
+                    newlines += (chars[i] == '\n') as usize;
+                    i += 1;
+                }
+                if newlines >= 2 {
+                    parser.push(Token::Paragraph);
+                } else {
+                    parser.space();
+                }
+                continue;
+            }
+            c => parser.word.push(c),
+        }
+        i += 1;
+    }
+    parser.flush();
+    while parser.page.tokens.last() == Some(&Token::Paragraph) {
+        parser.page.tokens.pop();
+    }
+    parser.page
+}
+
+pub struct PlacedWord {
+    pub text: String,
+    // Relativ zur linken oberen Ecke des Textblocks
+    pub x: i32,
+    pub y: i32,
+    pub color: Color,
+    // Ab wann der Typewriter das Wort tippt und wie schnell (Zeichen pro Sekunde)
+    pub start: f32,
+    pub speed: f32,
+}
+
+impl PlacedWord {
+    // So viel vom Wort, wie der Typewriter nach time Sekunden schon getippt hat
+    pub fn visible_text(&self, time: f32) -> &str {
+        if time < self.start {
+            return "";
+        }
+        let chars = ((time - self.start) * self.speed) as usize;
+        match self.text.char_indices().nth(chars) {
+            Some((end, _)) => &self.text[..end],
+            None => &self.text,
+        }
+    }
+}
+
+pub struct Layout {
+    pub words: Vec<PlacedWord>,
+    pub height: i32,
+    // Ab dann ist die ganze Seite getippt
+    pub duration: f32,
+    pub cues: Vec<(f32, Cue)>,
+}
+
+// Bricht die Seite auf max_width um, jede Zeile mittig, und legt fest, wann der Typewriter welches Wort tippt.
+// Teuer genug, um es nicht jeden Frame zu machen.
+pub fn layout(rl: &RaylibHandle, page: &Page, max_width: i32, font_size: i32, chars_per_second: f32) -> Layout {
+    let line_height = font_size + 5;
+    let space = rl.measure_text(" ", font_size);
+    let base_speed = chars_per_second.max(1.0);
+
+    let mut words = Vec::new();
+    let mut line: Vec<PlacedWord> = Vec::new();
+    let mut line_width = 0;
+    let mut y = 0;
+    let mut time = 0.0;
+    let mut speed = base_speed;
+    let mut cues = Vec::new();
+    let mut first_word = true;
+
+    let mut finish_line = |line: &mut Vec<PlacedWord>, line_width: &mut i32, y: &mut i32| {
+        let offset = (max_width - *line_width) / 2;
+        for mut word in line.drain(..) {
+            word.x += offset;
+            words.push(word);
+        }
+        *line_width = 0;
+        *y += line_height;
+    };
+
+    for token in page.tokens.iter() {
+        match token {
+            Token::Word { text, color, joined } => {
+                let width = rl.measure_text(text, font_size);
+                let gap = if line.is_empty() || *joined { 0 } else { space };
+                if !line.is_empty() && !*joined && line_width + gap + width > max_width {
+                    finish_line(&mut line, &mut line_width, &mut y);
+                }
+                let gap = if line.is_empty() || *joined { 0 } else { space };
+                // Das Leerzeichen davor braucht auch seine Zeit
+                if !*joined && !first_word {
+                    time += 1.0 / speed;
+                }
+                first_word = false;
+                line.push(PlacedWord {
+                    text: text.clone(),
+                    x: line_width + gap,
+                    y,
+                    color: *color,
+                    start: time,
+                    speed,
+                });
+                time += text.chars().count() as f32 / speed;
+                line_width += gap + width;
+            }
+            Token::LineBreak => finish_line(&mut line, &mut line_width, &mut y),
+            // Absatz: Zeile zu und eine Leerzeile
+            Token::Paragraph => {
+                if !line.is_empty() {
+                    finish_line(&mut line, &mut line_width, &mut y);
+                }
+                y += line_height;
+            }
+            Token::Pause(seconds) => time += seconds,
+            Token::Speed(factor) => speed = base_speed * factor,
+            Token::Cue(cue) => cues.push((time, cue.clone())),
+        }
+    }
+    if !line.is_empty() {
+        finish_line(&mut line, &mut line_width, &mut y);
+    }
+
+    Layout {
+        words,
+        height: y,
+        duration: time,
+        cues,
+    }
+}
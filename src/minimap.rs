@@ -0,0 +1,155 @@
+use raylib::prelude::*;
+
+use crate::item::{Item, ItemType};
+use crate::pathfinding::TilePos;
+use crate::tiled_map::{Tags, Tile, TiledMap};
+
+const MARGIN: f32 = 12.0;
+const MIN_CELL_SIZE: f32 = 2.0;
+const MAX_CELL_SIZE: f32 = 16.0;
+// Wie weit der Spieler um sich herum die Karte aufdeckt (in Tiles)
+const REVEAL_RADIUS: i32 = 3;
+
+pub struct Minimap {
+    pub visible: bool,
+    pub cell_size: f32,
+    explored: Vec<Vec<bool>>,
+}
+
+impl Minimap {
+    pub fn new(tiled_map: &TiledMap) -> Self {
+        Minimap {
+            visible: true,
+            cell_size: 8.0,
+            explored: vec![vec![false; tiled_map.size_y as usize]; tiled_map.size_x as usize],
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn zoom(&mut self, steps: f32) {
+        self.cell_size = (self.cell_size + steps).clamp(MIN_CELL_SIZE, MAX_CELL_SIZE);
+    }
+
+    pub fn explore(&mut self, tile: TilePos) {
+        for x in (tile.0 - REVEAL_RADIUS)..=(tile.0 + REVEAL_RADIUS) {
+            for y in (tile.1 - REVEAL_RADIUS)..=(tile.1 + REVEAL_RADIUS) {
+                if (x - tile.0).pow(2) + (y - tile.1).pow(2) > REVEAL_RADIUS.pow(2) {
+                    continue;
+                }
+                if let Some(cell) = self
+                    .explored
+                    .get_mut(x as usize)
+                    .and_then(|column| column.get_mut(y as usize))
+                {
+                    *cell = true;
+                }
+            }
+        }
+    }
+
+    fn is_explored(&self, tile: TilePos) -> bool {
+        tile.0 >= 0
+            && tile.1 >= 0
+            && self
+                .explored
+                .get(tile.0 as usize)
+                .and_then(|column| column.get(tile.1 as usize))
+                .copied()
+                .unwrap_or(false)
+    }
+
+    // Wird jedes Frame aus der aktuellen Map gezeichnet, weggehauene Hindernisse verschwinden also sofort
+    pub fn draw(
+        &self,
+        d: &mut RaylibDrawHandle,
+        tiled_map: &TiledMap,
+        player_feet: Vector2,
+        items: &[Item],
+    ) {
+        if !self.visible {
+            return;
+        }
+
+        let width = tiled_map.size_x as f32 * self.cell_size;
+        let height = tiled_map.size_y as f32 * self.cell_size;
+        let origin = Vector2::new(d.get_screen_width() as f32 - width - MARGIN, MARGIN);
+
+        d.draw_rectangle_rec(
+            Rectangle::new(origin.x - 2.0, origin.y - 2.0, width + 4.0, height + 4.0),
+            Color::BLACK.fade(0.6),
+        );
+
+        for x in 0..tiled_map.size_x {
+            for y in 0..tiled_map.size_y {
+                if !self.is_explored((x, y)) {
+                    continue;
+                }
+                let color = tiled_map
+                    .tiles_at(x, y)
+                    .filter_map(tile_color)
+                    .last()
+                    .unwrap_or(Color::BLANK);
+                d.draw_rectangle_rec(
+                    Rectangle::new(
+                        origin.x + x as f32 * self.cell_size,
+                        origin.y + y as f32 * self.cell_size,
+                        self.cell_size,
+                        self.cell_size,
+                    ),
+                    color,
+                );
+            }
+        }
+
+        let tile_size = Vector2::new(
+            tiled_map.tile_width as f32 * tiled_map.scale,
+            tiled_map.tile_height as f32 * tiled_map.scale,
+        );
+        let to_minimap = |pos: Vector2| {
+            Vector2::new(
+                origin.x + (pos.x - tiled_map.shift_x as f32) / tile_size.x * self.cell_size,
+                origin.y + (pos.y - tiled_map.shift_y as f32) / tile_size.y * self.cell_size,
+            )
+        };
+
+        for item in items {
+            if !self.is_explored(tiled_map.tile_at(item.center())) {
+                continue;
+            }
+            let color = match item.item_type {
+                ItemType::Gear => Color::GOLD,
+                _ => Color::WHITE,
+            };
+            d.draw_circle_v(to_minimap(item.center()), (self.cell_size / 3.0).max(1.5), color);
+        }
+
+        d.draw_circle_v(to_minimap(player_feet), (self.cell_size / 2.0).max(2.0), Color::RED);
+    }
+}
+
+fn tile_color(tile: &Tile) -> Option<Color> {
+    let id = match tile {
+        Tile::Static(id, _) => *id,
+        Tile::Animated(ids, _, _) => ids[0],
+        // Zerfällt gerade. Sobald die Barriere weg ist, sieht man wieder den Boden.
+        Tile::AnimatedOnce(ids, _, tags) if tags.contains(&Tags::Barrier) => ids[0],
+        Tile::AnimatedOnce(_, _, _) => return None,
+    };
+
+    if tile.tags().contains(&Tags::Goal) {
+        return Some(Color::GOLD);
+    }
+
+    match id {
+        1 => Some(Color::new(230, 205, 150, 255)),  // Sand
+        2 => Some(Color::new(40, 150, 60, 255)),    // Palme
+        7 => Some(Color::new(130, 130, 130, 255)),  // Stein
+        13 => Some(Color::new(40, 110, 200, 255)),  // Wasser
+        17 => Some(Color::new(150, 100, 50, 255)),  // Sandmauer
+        18 => Some(Color::new(200, 160, 80, 255)),  // Sandhaufen
+        _ => None,
+    }
+}
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+use crate::item::ItemType;
+
+// Was man zum Schiff zurückbringen muss, damit das Level als geschafft gilt
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Objective {
+    #[serde(default)]
+    pub parts: Vec<(ItemType, i32)>,
+}
+
+impl Objective {
+    pub fn required(&self) -> i32 {
+        self.parts.iter().map(|(_, count)| count).sum()
+    }
+
+    pub fn collected(&self, parts: &[ItemType]) -> i32 {
+        self.parts
+            .iter()
+            .map(|(part, count)| (parts.iter().filter(|p| *p == part).count() as i32).min(*count))
+            .sum()
+    }
+
+    pub fn missing(&self, parts: &[ItemType]) -> i32 {
+        self.required() - self.collected(parts)
+    }
+
+    pub fn is_met(&self, parts: &[ItemType]) -> bool {
+        self.missing(parts) <= 0
+    }
+}
@@ -0,0 +1,217 @@
+use rand::Rng;
+use raylib::prelude::*;
+
+use crate::texture_atlas::{SpriteRef, TextureAtlas};
+
+#[derive(Clone, Copy)]
+pub enum ParticleShape {
+    Square,
+    Circle,
+    Texture(SpriteRef),
+}
+
+// Beschreibt, wie Partikel aussehen und sich bewegen. Alle (min, max) Paare werden zufällig gewählt.
+#[derive(Clone)]
+pub struct EmitterConfig {
+    pub count: usize,
+    pub rate: f32, // Partikel pro Sekunde, nur für dauerhafte Emitter
+    pub lifetime: (f32, f32),
+    pub speed: (f32, f32),
+    pub angle: (f32, f32), // in Radiant, 0 = rechts, -PI/2 = oben
+    pub gravity: f32,
+    pub size: (f32, f32),
+    pub spread: Vector2, // halbe Größe des Bereichs, in dem Partikel entstehen
+    pub start_color: Color,
+    pub end_color: Color,
+    pub shape: ParticleShape,
+}
+
+impl Default for EmitterConfig {
+    fn default() -> Self {
+        EmitterConfig {
+            count: 10,
+            rate: 0.0,
+            lifetime: (0.5, 1.0),
+            speed: (50.0, 100.0),
+            angle: (0.0, std::f32::consts::TAU),
+            gravity: 0.0,
+            size: (2.0, 4.0),
+            spread: Vector2::zero(),
+            start_color: Color::WHITE,
+            end_color: Color::WHITE.fade(0.0),
+            shape: ParticleShape::Square,
+        }
+    }
+}
+
+struct Particle {
+    pos: Vector2,
+    velocity: Vector2,
+    life: f32,
+    max_life: f32,
+    size: f32,
+    gravity: f32,
+    start_color: Color,
+    end_color: Color,
+    shape: ParticleShape,
+}
+
+pub struct Emitter {
+    pub pos: Vector2,
+    pub config: EmitterConfig,
+    pub active: bool,
+    accumulator: f32,
+}
+
+impl Emitter {
+    pub fn new(pos: Vector2, config: EmitterConfig) -> Self {
+        Emitter {
+            pos,
+            config,
+            active: true,
+            accumulator: 0.0,
+        }
+    }
+}
+
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    emitters: Vec<Emitter>,
+}
+
+impl ParticleSystem {
+    pub fn new() -> Self {
+        ParticleSystem {
+            particles: Vec::new(),
+            emitters: Vec::new(),
+        }
+    }
+
+    pub fn add_emitter(&mut self, emitter: Emitter) -> usize {
+        self.emitters.push(emitter);
+        self.emitters.len() - 1
+    }
+
+    pub fn clear_emitters(&mut self) {
+        self.emitters.clear();
+    }
+
+    pub fn burst(&mut self, config: &EmitterConfig, pos: Vector2) {
+        for _ in 0..config.count {
+            self.spawn(config, pos);
+        }
+    }
+
+    fn spawn(&mut self, config: &EmitterConfig, pos: Vector2) {
+        let mut rng = rand::rng();
+        let mut range = |(min, max): (f32, f32)| {
+            if min < max {
+                rng.random_range(min..max)
+            } else {
+                min
+            }
+        };
+
+        let angle = range(config.angle);
+        let speed = range(config.speed);
+        let max_life = range(config.lifetime);
+        let offset = Vector2::new(
+            range((-config.spread.x, config.spread.x)),
+            range((-config.spread.y, config.spread.y)),
+        );
+        self.particles.push(Particle {
+            pos: pos + offset,
+            velocity: Vector2::new(angle.cos() * speed, angle.sin() * speed),
+            life: max_life,
+            max_life,
+            size: range(config.size),
+            gravity: config.gravity,
+            start_color: config.start_color,
+            end_color: config.end_color,
+            shape: config.shape,
+        });
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        let mut spawns: Vec<(EmitterConfig, Vector2)> = Vec::new();
+        for emitter in self.emitters.iter_mut().filter(|e| e.active && e.config.rate > 0.0) {
+            emitter.accumulator += delta_time * emitter.config.rate;
+            while emitter.accumulator >= 1.0 {
+                emitter.accumulator -= 1.0;
+                spawns.push((emitter.config.clone(), emitter.pos));
+            }
+        }
+        for (config, pos) in spawns {
+            self.spawn(&config, pos);
+        }
+
+        for particle in self.particles.iter_mut() {
+            particle.velocity.y += particle.gravity * delta_time;
+            particle.pos += particle.velocity.scale_by(delta_time);
+            particle.life -= delta_time;
+        }
+        self.particles.retain(|particle| particle.life > 0.0);
+    }
+
+    pub fn render(&self, d: &mut RaylibMode2D<'_, RaylibDrawHandle>, atlas: &TextureAtlas) {
+        for particle in self.particles.iter() {
+            let t = 1.0 - particle.life / particle.max_life;
+            let color = lerp_color(particle.start_color, particle.end_color, t);
+            match particle.shape {
+                ParticleShape::Square => d.draw_rectangle_v(
+                    particle.pos - Vector2::new(particle.size / 2.0, particle.size / 2.0),
+                    Vector2::new(particle.size, particle.size),
+                    color,
+                ),
+                ParticleShape::Circle => d.draw_circle_v(particle.pos, particle.size / 2.0, color),
+                ParticleShape::Texture(sprite) => {
+                    let scale = particle.size / sprite.width().max(1.0);
+                    atlas.draw_scaled(d, sprite, particle.pos, scale, color);
+                }
+            }
+        }
+    }
+}
+
+fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    Color::new(
+        channel(from.r, to.r),
+        channel(from.g, to.g),
+        channel(from.b, to.b),
+        channel(from.a, to.a),
+    )
+}
+
+pub fn sand_puff() -> EmitterConfig {
+    let sand = Color::new(220, 195, 140, 180);
+    EmitterConfig {
+        count: 5,
+        lifetime: (0.25, 0.5),
+        speed: (15.0, 45.0),
+        angle: (-std::f32::consts::PI, 0.0),
+        gravity: 40.0,
+        size: (3.0, 6.0),
+        spread: Vector2::new(8.0, 2.0),
+        start_color: sand,
+        end_color: sand.fade(0.0),
+        shape: ParticleShape::Circle,
+        ..Default::default()
+    }
+}
+
+pub fn water_splash() -> EmitterConfig {
+    EmitterConfig {
+        count: 6,
+        rate: 0.6,
+        lifetime: (0.4, 0.8),
+        speed: (30.0, 80.0),
+        angle: (-2.4, -0.7),
+        gravity: 220.0,
+        size: (2.0, 5.0),
+        spread: Vector2::new(24.0, 24.0),
+        start_color: Color::new(230, 245, 255, 230),
+        end_color: Color::new(120, 180, 255, 0),
+        shape: ParticleShape::Circle,
+    }
+}
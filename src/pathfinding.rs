@@ -0,0 +1,162 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::tiled_map::{Tags, Tile, TiledMap};
+
+pub type TilePos = (i32, i32);
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PathOptions {
+    // None: zerstörbare Hindernisse sind Wände. Some(cost): man kommt durch, kostet aber extra.
+    pub destroyable_cost: Option<u32>,
+    // Nur diese Hindernisse (Tile-ID) sind durchlässig, None heißt alle zerstörbaren
+    pub destroyable_ids: Option<Vec<i32>>,
+}
+
+impl PathOptions {
+    pub fn walkable() -> Self {
+        PathOptions::default()
+    }
+
+    pub fn through_destroyables(cost: u32) -> Self {
+        PathOptions {
+            destroyable_cost: Some(cost),
+            destroyable_ids: None,
+        }
+    }
+
+    pub fn only_destroying(ids: Vec<i32>, cost: u32) -> Self {
+        PathOptions {
+            destroyable_cost: Some(cost),
+            destroyable_ids: Some(ids),
+        }
+    }
+
+    fn can_pass(&self, tile: &Tile) -> bool {
+        if !tile.tags().contains(&Tags::Destroyable) {
+            return false;
+        }
+        match (&self.destroyable_ids, tile) {
+            (None, _) => true,
+            (Some(ids), Tile::Static(id, _)) => ids.contains(id),
+            (Some(_), _) => false,
+        }
+    }
+}
+
+// Mehr Wege merken wir uns nicht. Kreaturen, die dem Spieler hinterherlaufen, fragen bei jedem Tile
+// einen neuen Weg an, ohne Grenze würde der Cache einfach immer weiter wachsen.
+const MAX_CACHED_PATHS: usize = 256;
+
+// A* über alle Layer einer TiledMap. Ergebnisse werden gecached, bis sich die Map ändert
+// (TiledMap::revision, z.B. durch handle_hit_tiles) oder der Cache voll ist.
+pub struct Pathfinder {
+    cache: HashMap<(TilePos, TilePos, PathOptions), Option<Vec<TilePos>>>,
+    revision: u64,
+}
+
+impl Pathfinder {
+    pub fn new() -> Self {
+        Pathfinder {
+            cache: HashMap::new(),
+            revision: 0,
+        }
+    }
+
+    pub fn find_path(
+        &mut self,
+        tiled_map: &TiledMap,
+        start: TilePos,
+        goal: TilePos,
+        options: &PathOptions,
+    ) -> Option<Vec<TilePos>> {
+        if tiled_map.revision() != self.revision {
+            self.cache.clear();
+            self.revision = tiled_map.revision();
+        }
+
+        let key = (start, goal, options.clone());
+        if let Some(path) = self.cache.get(&key) {
+            return path.clone();
+        }
+
+        let path = a_star(tiled_map, start, goal, options);
+        if self.cache.len() >= MAX_CACHED_PATHS {
+            self.cache.clear();
+        }
+        self.cache.insert(key, path.clone());
+        path
+    }
+}
+
+// Kosten, um ein Tile zu betreten. None heißt nicht begehbar.
+pub fn tile_cost(tiled_map: &TiledMap, tile: TilePos, options: &PathOptions) -> Option<u32> {
+    if tile.0 < 0 || tile.1 < 0 || tile.0 >= tiled_map.size_x || tile.1 >= tiled_map.size_y {
+        return None;
+    }
+
+    let mut cost = 1;
+    for t in tiled_map.tiles_at(tile.0, tile.1) {
+        if !t.tags().contains(&Tags::Barrier) {
+            continue;
+        }
+        match options.destroyable_cost {
+            Some(extra) if options.can_pass(t) => cost += extra,
+            _ => return None,
+        }
+    }
+    Some(cost)
+}
+
+fn heuristic(a: TilePos, b: TilePos) -> u32 {
+    ((a.0 - b.0).abs() + (a.1 - b.1).abs()) as u32
+}
+
+fn a_star(
+    tiled_map: &TiledMap,
+    start: TilePos,
+    goal: TilePos,
+    options: &PathOptions,
+) -> Option<Vec<TilePos>> {
+    tile_cost(tiled_map, goal, options)?;
+
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<TilePos, TilePos> = HashMap::new();
+    let mut costs: HashMap<TilePos, u32> = HashMap::new();
+
+    costs.insert(start, 0);
+    open.push(Reverse((heuristic(start, goal), 0, start)));
+
+    while let Some(Reverse((_, cost, current))) = open.pop() {
+        if current == goal {
+            let mut path = vec![current];
+            let mut node = current;
+            while let Some(previous) = came_from.get(&node) {
+                node = *previous;
+                path.push(node);
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        // Schon über einen besseren Weg gefunden
+        if costs.get(&current).is_some_and(|best| *best < cost) {
+            continue;
+        }
+
+        for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            let next = (current.0 + dx, current.1 + dy);
+            let Some(step) = tile_cost(tiled_map, next, options) else {
+                continue;
+            };
+            let next_cost = cost + step;
+            if costs.get(&next).is_none_or(|best| next_cost < *best) {
+                costs.insert(next, next_cost);
+                came_from.insert(next, current);
+                open.push(Reverse((next_cost + heuristic(next, goal), next_cost, next)));
+            }
+        }
+    }
+
+    None
+}
@@ -1,57 +1,78 @@
 use crate::{
-    audiomanager::AudioManager, item::Item, texture_atlas::TextureAtlas, tiled_map::{self, Tags, Tile, TiledMap}, tool::Tool, trait_collision::Collision
+    audiomanager::AudioManager, inventory::{BACKPACK_SLOTS, Inventory}, item::{Item, ItemType}, animation::{Animation, AnimationClip}, asset_manifest::HpTierConfig, texture_atlas::TextureAtlas, tiled_map::{self, Tags, Tile, TiledMap}, tool::Tool, trait_collision::Collision
 };
 use raylib::prelude::*;
 
 const SCALE: f32 = 2.0;
-
-#[derive(Clone)]
-pub struct Animation<'a> {
-    frames: &'a Vec<&'a Texture2D>,
-    pub current: &'a Texture2D,
-    pub index: usize,
+// So lange blinkt der Spieler rot, nachdem ihn was erwischt hat
+const HURT_DURATION: f32 = 0.4;
+// Laufgeschwindigkeit bei speed 1.0 in der HP-Stufe
+const BASE_SPEED: f32 = 300.0;
+// Alle so viele Pixel gibt es einen Schritt (bei voller Geschwindigkeit ca. 4 pro Sekunde)
+const STEP_LENGTH: f32 = 75.0;
+
+// Eine HP-Stufe aus dem Manifest: eigene Animationen und Geschwindigkeit, solange HP > above * max_hp
+pub struct HpTier {
+    above: f32,
+    idle: Animation,
+    run: Animation,
+    speed: f32,
 }
 
-impl Animation<'_> {
-    pub fn new<'a>(frames: &'a Vec<&Texture2D>) -> Animation<'a> {
-        Animation {
-            frames,
-            current: &frames[0],
-            index: 0,
-        }
-    }
-
-    pub fn update(&mut self) {
-        self.current = self.frames.get(self.index).unwrap_or(&self.frames[0]);
-
-        self.index += 1;
-        if self.index >= self.frames.len() {
-            self.index = 0;
+impl HpTier {
+    pub fn new(config: &HpTierConfig, atlas: &TextureAtlas) -> HpTier {
+        HpTier {
+            above: config.above,
+            idle: Animation::new(&atlas.get_animation(&config.idle)),
+            run: Animation::new(&atlas.get_animation(&config.run)),
+            speed: config.speed,
         }
     }
 }
 
-pub struct Player<'a> {
+pub struct Player {
     pub pos: Vector2,
     pub dimensions: Vector2,
     pub movement: Movement,
-    pub idle: (
-        Animation<'a>, // high hp
-        Animation<'a>, //   ^
-        Animation<'a>, //   |
-        Animation<'a>, //   |
-        Animation<'a>, // low hp
-    ),
-    pub run: (
-        Animation<'a>, // high hp
-        Animation<'a>, //   ^
-        Animation<'a>, //   |
-        Animation<'a>, //   |
-        Animation<'a>, // low hp
-    ),
+    // Von viel HP nach wenig HP sortiert
+    tiers: Vec<HpTier>,
+    // Schwingen, Getroffen werden und Sterben haben je eine eigene Animation, egal wie viel HP
+    swing: Animation,
+    hurt: Animation,
+    death: Animation,
+    state: PlayerState,
+    state_timer: f32,
+    // Strecke seit dem letzten Schritt
+    step_distance: f32,
+    footstep: bool,
     orientation: Orientation,
-    inventory: Inventory<'a>, // Wieso überall gleiche LIfetime, ich verstreh nichts hier ist doof und dieser Kommentar ist auch ziemlich lang irgendwie formatiert er das nicht WTH
+    inventory: Inventory, // Wieso überall gleiche LIfetime, ich verstreh nichts hier ist doof und dieser Kommentar ist auch ziemlich lang irgendwie formatiert er das nicht WTH
     pub hp: i32,
+    pub max_hp: i32,
+    pub parts: Vec<ItemType>,
+}
+
+pub enum PlayerStatus {
+    Alive,
+    AtGoal,
+    Dead,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlayerState {
+    Idle,
+    Run,
+    // Holt mit dem Tool aus, so lange kann man nicht laufen
+    Swing,
+    Hurt,
+    // Spielt die Todesanimation, erst danach ist das Spiel vorbei
+    Dead,
+}
+
+impl PlayerState {
+    fn locks_movement(&self) -> bool {
+        matches!(self, PlayerState::Swing | PlayerState::Dead)
+    }
 }
 
 #[derive(Clone)]
@@ -60,69 +81,71 @@ pub enum Orientation {
     Right,
 }
 
-enum Inventory<'a> {
-    Empty,
-    Left(Tool<'a>),
-    Right(Tool<'a>),
-    Both(Tool<'a>, Tool<'a>),
-}
-
-impl<'a> Player<'a> {
+impl Player {
     pub fn new(
         pos: Vector2,
-        idle: (
-            &'a Vec<&Texture2D>,
-            &'a Vec<&Texture2D>,
-            &'a Vec<&Texture2D>,
-            &'a Vec<&Texture2D>,
-            &'a Vec<&Texture2D>,
-        ),
-        run: (
-            &'a Vec<&Texture2D>,
-            &'a Vec<&Texture2D>,
-            &'a Vec<&Texture2D>,
-            &'a Vec<&Texture2D>,
-            &'a Vec<&Texture2D>,
-        ),
-    ) -> Player<'a> {
+        tiers: Vec<HpTier>,
+        max_hp: i32,
+        swing: &AnimationClip,
+        hurt: &AnimationClip,
+        death: &AnimationClip,
+    ) -> Player {
+        if tiers.is_empty() {
+            panic!("player needs at least one hp tier");
+        }
+        // Groß genug für den größten Frame, kleinere werden unten mittig reingestellt
+        let (width, height) = tiers
+            .iter()
+            .flat_map(|tier| [tier.idle.size(), tier.run.size()])
+            .fold((0.0f32, 0.0f32), |(w, h), (fw, fh)| (w.max(fw), h.max(fh)));
         Player {
             pos,
-            dimensions: Vector2::new(idle.0[0].width() as f32, idle.0[0].height() as f32),
+            dimensions: Vector2::new(width, height),
             movement: Movement {
                 direction: Vector2 { x: 0.0, y: 0.0 },
-                speed: 300.0,
+                speed: BASE_SPEED,
             },
-            idle: (
-                Animation::new(idle.0),
-                Animation::new(idle.1),
-                Animation::new(idle.2),
-                Animation::new(idle.3),
-                Animation::new(idle.4),
-            ),
-            run: (
-                Animation::new(run.0),
-                Animation::new(run.1),
-                Animation::new(run.2),
-                Animation::new(run.3),
-                Animation::new(run.4),
-            ),
+            tiers,
+            swing: Animation::new(swing),
+            hurt: Animation::new(hurt),
+            death: Animation::new(death),
+            state: PlayerState::Idle,
+            state_timer: 0.0,
+            step_distance: STEP_LENGTH,
+            footstep: false,
             orientation: Orientation::Right,
-            inventory: Inventory::Empty,
-            hp: 1000,
+            inventory: Inventory::new(BACKPACK_SLOTS),
+            hp: max_hp,
+            max_hp,
+            parts: Vec::new(),
         }
     }
 
     pub fn get_collision_rect(&self) -> Rectangle {
+        self.collision_rect_at(self.pos)
+    }
+
+    pub fn collision_rect_at(&self, pos: Vector2) -> Rectangle {
         Rectangle::new(
-            self.pos.x + 12.0,
-            self.pos.y + 0.8 * SCALE * self.dimensions.y,
+            pos.x + 12.0,
+            pos.y + 0.8 * SCALE * self.dimensions.y,
             self.dimensions.x * SCALE - 24.0,
             self.dimensions.y * SCALE * 0.2,
         )
     }
 
+    pub fn feet(&self) -> Vector2 {
+        let rect = self.get_collision_rect();
+        Vector2::new(rect.x + rect.width / 2.0, rect.y + rect.height)
+    }
+
     pub fn get_tool_collision_rect(&self) -> Rectangle {
-        return match self.orientation {
+        self.tool_collision_rect(&self.orientation)
+    }
+
+    // Jede Hand trifft auf ihrer Seite, egal wohin der Spieler inzwischen schaut
+    pub fn tool_collision_rect(&self, hand: &Orientation) -> Rectangle {
+        return match hand {
             Orientation::Left => Rectangle::new(
                 self.pos.x - 16.0 * SCALE,
                 (self.pos.y + 24.0) + 0.5 * SCALE,
@@ -138,228 +161,162 @@ impl<'a> Player<'a> {
         };
     }
 
-    pub fn update(&mut self, frame_time: f32, tiled_map: &TiledMap) -> bool {
-        let old_pos = self.pos.clone();
-        let old_old_pos = self.pos.clone();
+    // Die erste Stufe, über deren Schwelle die HP noch liegen. Darunter gilt immer die letzte.
+    fn current_tier(&mut self) -> &mut HpTier {
+        let index = self.tier_index();
+        &mut self.tiers[index]
+    }
+
+    fn tier_index(&self) -> usize {
+        let hp = self.hp as f32;
+        let max_hp = self.max_hp as f32;
+        self.tiers
+            .iter()
+            .position(|tier| hp > tier.above * max_hp)
+            .unwrap_or(self.tiers.len() - 1)
+    }
+
+    pub fn state(&self) -> PlayerState {
+        self.state
+    }
+
+    fn set_state(&mut self, state: PlayerState) {
+        if self.state == state {
+            return;
+        }
+        self.exit_state(self.state);
+        self.state = state;
+        self.enter_state(state);
+    }
+
+    fn enter_state(&mut self, state: PlayerState) {
+        self.state_timer = 0.0;
+        match state {
+            PlayerState::Idle | PlayerState::Run => (),
+            PlayerState::Swing => self.swing.restart(),
+            PlayerState::Hurt => self.hurt.restart(),
+            PlayerState::Dead => {
+                self.hp = 0;
+                self.movement.reset();
+                self.death.restart();
+            }
+        }
+    }
+
+    fn exit_state(&mut self, state: PlayerState) {
+        match state {
+            // Wer beim Schwingen unterbrochen wird (z.B. getroffen), schlägt nicht mehr zu
+            PlayerState::Swing => {
+                for tool in self.inventory.hands_mut() {
+                    tool.cancel();
+                }
+            }
+            _ => (),
+        }
+    }
 
-        if self.hp <= 200 {
-            self.movement.speed = 150.0;
+    // Schaden von außen (Krabben, ...). Tote kann man nicht mehr verletzen.
+    pub fn hurt(&mut self, damage: i32) {
+        if damage <= 0 || self.state == PlayerState::Dead {
+            return;
+        }
+        self.hp -= damage;
+        if self.hp <= 0 {
+            self.set_state(PlayerState::Dead);
         } else {
-            self.movement.speed = 300.0;
+            self.set_state(PlayerState::Hurt);
+        }
+    }
+
+    pub fn update(&mut self, frame_time: f32, tiled_map: &TiledMap) -> PlayerStatus {
+        self.state_timer += frame_time;
+        match self.state {
+            PlayerState::Dead if self.death.is_finished() => return PlayerStatus::Dead,
+            PlayerState::Dead => return PlayerStatus::Alive,
+            PlayerState::Hurt if self.state_timer >= HURT_DURATION => self.set_state(PlayerState::Idle),
+            _ => (),
+        }
+        if self.state.locks_movement() {
+            self.movement.reset();
+        }
+        if matches!(self.state, PlayerState::Idle | PlayerState::Run) {
+            let next = if self.movement.moves() {
+                PlayerState::Run
+            } else {
+                PlayerState::Idle
+            };
+            self.set_state(next);
         }
 
-        self.pos.x = old_pos.x;
-        self.pos.y = old_pos.y;
+        let old_pos = self.pos.clone();
+
+        self.movement.speed = BASE_SPEED * self.current_tier().speed;
 
-        self.pos += self
+        let delta = self
             .movement
             .direction
             .normalized()
             .scale_by(self.movement.speed)
             .scale_by(frame_time);
+        self.pos = tiled_map.slide(old_pos, delta, |pos| self.collision_rect_at(pos));
 
-        let mut collided = false;
-        let mut collision_tiles: Vec<(&Tile, Vector2)> = vec![];
+        let mut at_goal = false;
         for layer in 0..tiled_map.layers {
-            tiled_map
-                .get_collision_tiles_with_layer(layer, &self.get_collision_rect())
-                .map(|mut t| collision_tiles.append(&mut t));
-        }
-        for (tile, _pos) in collision_tiles {
-            match tile {
-                tiled_map::Tile::Static(_, tags) if tags.contains(&Tags::Goal) => {
-                    return true;
-                }
-                tiled_map::Tile::Static(_, tags) if tags.contains(&Tags::Barrier) => {
-                    self.pos.x = old_pos.x;
-                    self.pos.y = old_pos.y;
-                    collided = true;
-                }
-                tiled_map::Tile::Animated(_, _, tags) if tags.contains(&Tags::Barrier) => {
-                    self.pos.x = old_pos.x;
-                    self.pos.y = old_pos.y;
-                    collided = true;
-                }
-                tiled_map::Tile::AnimatedOnce(_, _, tags) if tags.contains(&Tags::Barrier) => {
-                    self.pos.x = old_pos.x;
-                    self.pos.y = old_pos.y;
-                    collided = true;
-                }
-                _ => (),
+            if let Some(tiles) =
+                tiled_map.get_collision_tiles_with_layer(layer, &self.get_collision_rect())
+            {
+                at_goal |= tiles.iter().any(|(tile, _)| tile.tags().contains(&Tags::Goal));
             }
         }
 
-        if collided {
-            collided = false;
-            self.pos.x = old_pos.x;
-            self.pos.y = old_pos.y;
-            self.pos += self
-                .movement
-                .direction
-                .normalized()
-                .scale_by(self.movement.speed)
-                .scale_by(frame_time);
-            self.pos.x = old_pos.x;
-            let mut collision_tiles: Vec<(&Tile, Vector2)> = vec![];
-            for layer in 0..tiled_map.layers {
-                tiled_map
-                    .get_collision_tiles_with_layer(layer, &self.get_collision_rect())
-                    .map(|mut t| collision_tiles.append(&mut t));
-            }
-            for (tile, _pos) in collision_tiles {
-                match tile {
-                    tiled_map::Tile::Static(_, tags) if tags.contains(&Tags::Barrier) => {
-                        self.pos.x = old_pos.x;
-                        self.pos.y = old_pos.y;
-                        collided = true;
-                    }
-                    tiled_map::Tile::Animated(_, _, tags) if tags.contains(&Tags::Barrier) => {
-                        self.pos.x = old_pos.x;
-                        self.pos.y = old_pos.y;
-                        collided = true;
-                    }
-                    tiled_map::Tile::AnimatedOnce(_, _, tags) if tags.contains(&Tags::Barrier) => {
-                        self.pos.x = old_pos.x;
-                        self.pos.y = old_pos.y;
-                        collided = true;
-                    }
-                    _ => (),
-                }
-            }
-        }
+        self.inventory.remove_broken();
 
-        if collided {
-            self.pos.x = old_pos.x;
-            self.pos.y = old_pos.y;
-            self.pos += self
-                .movement
-                .direction
-                .normalized()
-                .scale_by(self.movement.speed)
-                .scale_by(frame_time);
-            self.pos.y = old_pos.y;
-            let mut collision_tiles: Vec<(&Tile, Vector2)> = vec![];
-            for layer in 0..tiled_map.layers {
-                tiled_map
-                    .get_collision_tiles_with_layer(layer, &self.get_collision_rect())
-                    .map(|mut t| collision_tiles.append(&mut t));
-            }
-            for (tile, _pos) in collision_tiles {
-                match tile {
-                    tiled_map::Tile::Static(_, tags) if tags.contains(&Tags::Barrier) => {
-                        self.pos.y = old_pos.y;
-                    }
-                    tiled_map::Tile::Animated(_, _, tags) if tags.contains(&Tags::Barrier) => {
-                        self.pos.y = old_pos.y;
-                    }
-                    tiled_map::Tile::AnimatedOnce(_, _, tags) if tags.contains(&Tags::Barrier) => {
-                        self.pos.y = old_pos.y;
-                    }
-                    _ => (),
-                }
+        // Nur was wirklich gelaufen wurde zählt, gegen die Wand drücken macht keine Schritte.
+        // Im Stand aufgeladen, damit der erste Schritt sofort kommt.
+        let moved = (self.pos - old_pos).length();
+        if moved > 0.0 {
+            self.step_distance += moved;
+            if self.step_distance >= STEP_LENGTH {
+                self.step_distance = 0.0;
+                self.footstep = true;
             }
+        } else {
+            self.step_distance = STEP_LENGTH;
         }
 
-        let mut collision_tiles: Vec<(&Tile, Vector2)> = vec![];
-        for layer in 0..tiled_map.layers {
-            tiled_map
-                .get_collision_tiles_with_layer(layer, &self.get_collision_rect())
-                .map(|mut t| collision_tiles.append(&mut t));
-        }
-        for (tile, _pos) in collision_tiles {
-            match tile {
-                tiled_map::Tile::Static(_, tags) if tags.contains(&Tags::Barrier) => {
-                    self.pos.x = old_pos.x;
-                    self.pos.y = old_pos.y;
-                }
-                tiled_map::Tile::Animated(_, _, tags) if tags.contains(&Tags::Barrier) => {
-                    self.pos.x = old_pos.x;
-                    self.pos.y = old_pos.y;
-                }
-                tiled_map::Tile::AnimatedOnce(_, _, tags) if tags.contains(&Tags::Barrier) => {
-                    self.pos.x = old_pos.x;
-                    self.pos.y = old_pos.y;
-                }
-                _ => (),
+        if self.pos != old_pos {
+            self.hp -= 1;
+            if self.hp <= 0 {
+                self.set_state(PlayerState::Dead);
+                return PlayerStatus::Alive;
             }
         }
 
-        self.inventory = match &self.inventory {
-            Inventory::Empty => Inventory::Empty,
-            Inventory::Left(l) => match l {
-                Tool::Axe(orientation, animation, u, b) if *u == 0 && !b => Inventory::Empty,
-                Tool::Pickaxe(orientation, animation, u, b) if *u == 0 && !b => Inventory::Empty,
-                Tool::Shovel(orientation, animation, u, b) if *u == 0 && !b => Inventory::Empty,
-                _ => Inventory::Left(l.clone()),
-            },
-            Inventory::Right(r) => match r {
-                Tool::Axe(orientation, animation, u, b) if *u == 0 && !b => Inventory::Empty,
-                Tool::Pickaxe(orientation, animation, u, b) if *u == 0 && !b => Inventory::Empty,
-                Tool::Shovel(orientation, animation, u, b) if *u == 0 && !b => Inventory::Empty,
-                _ => Inventory::Right(r.clone()),
-            },
-            Inventory::Both(l, r) => match l {
-                Tool::Axe(orientation, animation, u, b) if *u == 0 && !b => {
-                    Inventory::Right(r.clone())
-                }
-                Tool::Pickaxe(orientation, animation, u, b) if *u == 0 && !b => {
-                    Inventory::Right(r.clone())
-                }
-                Tool::Shovel(orientation, animation, u, b) if *u == 0 && !b => {
-                    Inventory::Right(r.clone())
-                }
-                _ => match r {
-                    Tool::Axe(orientation, animation, u, b) if *u == 0 && !b => {
-                        Inventory::Left(l.clone())
-                    }
-                    Tool::Pickaxe(orientation, animation, u, b) if *u == 0 && !b => {
-                        Inventory::Left(l.clone())
-                    }
-                    Tool::Shovel(orientation, animation, u, b) if *u == 0 && !b => {
-                        Inventory::Left(l.clone())
-                    }
-                    _ => Inventory::Both(l.clone(), r.clone()),
-                },
-            },
-        };
-
-        if self.pos != old_old_pos {
-            if self.hp.is_positive() {
-                self.hp -= 1;
-            } else {
-                return true;
-            }
+        if at_goal {
+            return PlayerStatus::AtGoal;
         }
+        return PlayerStatus::Alive;
+    }
 
-        return false;
+    // Ob im letzten update() ein Schritt dran war. Wie er klingt, hängt vom Boden ab (TiledMap::footstep_at).
+    pub fn take_footstep(&mut self) -> bool {
+        std::mem::take(&mut self.footstep)
     }
 
-    pub fn use_tool(&mut self, tiled_map: &TiledMap, audio_manager: &mut AudioManager) -> Vec<(Tile, Vector2)> {
-        let mut used_tool;
-        match (&self.orientation, &mut self.inventory) {
-            (Orientation::Left, Inventory::Left(l)) => {
-                l.use_tool();
-            }
-            (Orientation::Right, Inventory::Right(r)) => {
-                r.use_tool();
-            }
-            (Orientation::Right, Inventory::Both(_, r)) => {
-                r.use_tool();
-            }
-            (Orientation::Left, Inventory::Both(l, _)) => {
-                l.use_tool();
-            }
-            _ => (),
+    // Holt nur aus, getroffen wird erst beim "hit"-Frame der Animation (siehe animation_update)
+    pub fn use_tool(&mut self) {
+        if !matches!(self.state, PlayerState::Idle | PlayerState::Run | PlayerState::Hurt) {
+            return;
         }
-
-        let coll_rec = self.get_tool_collision_rect();
-        match (&self.orientation, &mut self.inventory) {
-            (Orientation::Left, Inventory::Left(l)) => used_tool = Some(l),
-            (Orientation::Right, Inventory::Right(r)) => used_tool = Some(r),
-            (Orientation::Right, Inventory::Both(_, r)) => used_tool = Some(r),
-            (Orientation::Left, Inventory::Both(l, _)) => used_tool = Some(l),
-            _ => used_tool = None,
+        if let Some(tool) = self.inventory.hand(&self.orientation) {
+            tool.use_tool();
+            self.set_state(PlayerState::Swing);
         }
+    }
+
+    pub fn tool_hit(&mut self, hand: &Orientation, tiled_map: &TiledMap, audio_manager: &mut AudioManager) -> Vec<(Tile, Vector2)> {
+        let coll_rec = self.tool_collision_rect(hand);
+        let mut used_tool = self.inventory.hand(hand);
 
         let mut tool_collision_tiles: Vec<(&Tile, Vector2)> = vec![];
         for layer in 0..tiled_map.layers {
@@ -373,20 +330,20 @@ impl<'a> Player<'a> {
             match tile {
                 Tile::Static(id, tags) if tags.contains(&Tags::Destroyable) => {
                     match &mut used_tool {
-                        Some(Tool::Axe(orientation, animation, u, _)) if id == &2 => {
+                        Some(Tool::Axe(orientation, animation, u, _)) if id == &2 && *u > 0 => {
                             marked_tiles.push((tile.clone(), pos));
-                            audio_manager.play_sound("hit_wood");
-                            *u = 0;
+                            audio_manager.play_sound_at("hit_wood", pos);
+                            *u -= 1;
                         }
-                        Some(Tool::Pickaxe(orientation, animation, u, _)) if id == &7 => {
+                        Some(Tool::Pickaxe(orientation, animation, u, _)) if id == &7 && *u > 0 => {
                             marked_tiles.push((tile.clone(), pos));
-                            audio_manager.play_sound("hit_stone");
-                            *u = 0;
+                            audio_manager.play_sound_at("hit_stone", pos);
+                            *u -= 1;
                         }
-                        Some(Tool::Shovel(orientation, animation, u, _)) if id == &18 => {
+                        Some(Tool::Shovel(orientation, animation, u, _)) if id == &18 && *u > 0 => {
                             marked_tiles.push((tile.clone(), pos));
-                            audio_manager.play_sound("hit_sand");
-                            *u = 0;
+                            audio_manager.play_sound_at("hit_sand", pos);
+                            *u -= 1;
                         }
                         _ => (),
                     };
@@ -398,112 +355,105 @@ impl<'a> Player<'a> {
         return marked_tiles;
     }
 
-    pub fn animation_update(&mut self) {
+    // Gibt die Hände zurück, deren Tool gerade zugeschlagen hat, auch beide im selben Frame
+    pub fn animation_update(&mut self, delta_time: f32) -> Vec<Orientation> {
         // Hallo
         // ich weiß das das alles kagge is aber jetzt erst Recht, der ganze Code ist eh schon doof
         // flyingpower guave ist gar nicht so lecker, der ist ziemlich süß eigentlich :/
         // TODO: Bessere Getränke kaufen
-        if self.hp > (0.8 * 1000.0) as i32 {
-            self.idle.0.update();
-            self.run.0.update();
-        } else if self.hp > (0.6 * 1000.0) as i32 {
-            self.idle.1.update();
-            self.run.1.update();
-        } else if self.hp > (0.4 * 1000.0) as i32 {
-            self.idle.2.update();
-            self.run.2.update();
-        } else if self.hp > (0.2 * 1000.0) as i32 {
-            self.idle.3.update();
-            self.run.3.update();
-        } else {
-            self.idle.4.update();
-            self.run.4.update();
+        let tier = self.current_tier();
+        tier.idle.update(delta_time);
+        tier.run.update(delta_time);
+
+        match self.state {
+            PlayerState::Swing => {
+                self.swing.update(delta_time);
+            }
+            PlayerState::Hurt => {
+                self.hurt.update(delta_time);
+            }
+            PlayerState::Dead => {
+                self.death.update(delta_time);
+            }
+            PlayerState::Idle | PlayerState::Run => (),
         }
 
-        match &mut self.inventory {
-            Inventory::Left(l) => l.update(),
-            Inventory::Right(r) => r.update(),
-            Inventory::Both(l, r) => {
-                l.update();
-                r.update();
+        let mut hits = Vec::new();
+        for hand in [Orientation::Left, Orientation::Right] {
+            if let Some(tool) = self.inventory.hand(&hand) {
+                if tool.update(delta_time) {
+                    hits.push(hand);
+                }
             }
-            _ => (),
         }
+
+        // Fertig geschwungen, sobald das Tool in der Hand wieder still ist
+        if self.state == PlayerState::Swing
+            && !self.inventory.hand(&self.orientation).is_some_and(|tool| tool.is_running())
+        {
+            self.set_state(PlayerState::Idle);
+        }
+        hits
     }
 
     pub fn draw(
         &mut self,
         d: &mut RaylibMode2D<RaylibDrawHandle>,
+        atlas: &TextureAtlas,
         delta_time: f32,
         elapsed_time: f32,
     ) {
-        let texture;
-
-        if self.hp > (0.8 * 1000.0) as i32 {
-            if self.movement.moves() {
-                texture = &self.run.0.current;
-            } else {
-                texture = &self.idle.0.current;
-            }
-        } else if self.hp > (0.6 * 1000.0) as i32 {
-            if self.movement.moves() {
-                texture = &self.run.1.current;
-            } else {
-                texture = &self.idle.1.current;
-            }
-        } else if self.hp > (0.4 * 1000.0) as i32 {
-            if self.movement.moves() {
-                texture = &self.run.2.current;
-            } else {
-                texture = &self.idle.2.current;
-            }
-        } else if self.hp > (0.2 * 1000.0) as i32 {
-            if self.movement.moves() {
-                texture = &self.run.3.current;
-            } else {
-                texture = &self.idle.3.current;
-            }
+        let animation;
+        let running = self.state == PlayerState::Run;
+
+        if self.state == PlayerState::Swing {
+            animation = &self.swing;
+        } else if self.state == PlayerState::Hurt {
+            animation = &self.hurt;
+        } else if self.state == PlayerState::Dead {
+            animation = &self.death;
+        } else if running {
+            animation = &self.tiers[self.tier_index()].run;
         } else {
-            if self.movement.moves() {
-                texture = &self.run.4.current;
-            } else {
-                texture = &self.idle.4.current;
-            }
+            animation = &self.tiers[self.tier_index()].idle;
         }
+        let texture = animation.current();
 
-        let rotation_modifier = match self.orientation {
-            Orientation::Left => -1,
-            Orientation::Right => 1,
+        let flip = match self.orientation {
+            Orientation::Left => true,
+            Orientation::Right => false,
+        };
+        // Getroffen: rot blinken
+        let tint = if self.state == PlayerState::Hurt && (self.state_timer * 30.0).sin() > 0.0 {
+            Color::RED
+        } else {
+            Color::WHITE
         };
 
 
-        let width = texture.width();
-        let height = texture.height();
-        let target_width = width;
-        let target_height = height;
+        // Kleinere Frames (z.B. ohne Schaden) stehen unten mittig, wie die großen
+        let (target_width, target_height) = animation.size();
+        let offset = Vector2::new(
+            (self.dimensions.x - target_width) / 2.0,
+            self.dimensions.y - target_height,
+        );
         //let mut target_width = 16;
         //let mut target_height = 24;
         //if width > height {
         //    target_width = 24;
         //    target_height = 16;
         //}
-        d.draw_texture_pro(
+        atlas.draw(
+            d,
             texture,
             Rectangle::new(
-                0.0,
-                0.0,
-                (rotation_modifier * texture.width()) as f32,
-                texture.height() as f32,
+                self.pos.x + offset.x * SCALE,
+                self.pos.y + offset.y * SCALE,
+                target_width * SCALE,
+                target_height * SCALE,
             ),
-            Rectangle::new(
-                self.pos.x,
-                self.pos.y,
-                (target_width as f32 * SCALE) as f32,
-                (target_height as f32 * SCALE) as f32,
-            ),
-            Vector2::zero(),
-            0 as f32,
-            Color::WHITE,
+            flip,
+            tint,
         );
 
         let tmp = self.get_collision_rect();
@@ -525,90 +475,53 @@ impl<'a> Player<'a> {
 
         // Draw items
         let offset = 20.0;
-        match &mut self.inventory {
-            Inventory::Left(l) => {
-                l.render(d, self.pos, elapsed_time, delta_time);
-            }
-            Inventory::Right(r) => {
-                r.render(d, self.pos, elapsed_time, delta_time);
-            }
-            Inventory::Both(l, r) => {
-                l.render(d, self.pos, elapsed_time, delta_time);
-                r.render(d, self.pos, elapsed_time, delta_time);
-            }
-            _ => (),
-        };
+        if self.state == PlayerState::Dead {
+            return;
+        }
+        for tool in self.inventory.hands_mut() {
+            tool.render(d, atlas, self.pos, elapsed_time, delta_time);
+        }
+    }
+
+    // Alle Tools in Händen und Rucksack mit ihren übrigen Benutzungen
+    pub fn tool_uses(&self) -> Vec<(ItemType, i32)> {
+        self.inventory.tools().map(|tool| (tool.item_type(), tool.uses_left())).collect()
+    }
+
+    pub fn draw_hud(&self, d: &mut RaylibDrawHandle, atlas: &TextureAtlas) {
+        self.inventory.draw_hud(d, atlas, &self.orientation);
     }
 
     pub fn add_tool(
         &mut self,
-        item: &Item<'a>,
+        item: &Item,
         atlas: &TextureAtlas,
-        axe_frames: &'a Vec<&Texture2D>,
-        pickaxe_frames: &'a Vec<&Texture2D>,
-        shovel_frames: &'a Vec<&Texture2D>,
+        axe_frames: &AnimationClip,
+        pickaxe_frames: &AnimationClip,
+        shovel_frames: &AnimationClip,
     ) -> bool {
         let tool = match item.item_type {
             crate::item::ItemType::Axe => {
-                Tool::Axe(Orientation::Left, Animation::new(&axe_frames), 1, false)
+                Tool::Axe(Orientation::Left, Animation::new(axe_frames), item.uses, false)
             }
 
             crate::item::ItemType::Pickaxe => Tool::Pickaxe(
                 Orientation::Right,
-                Animation::new(&pickaxe_frames),
-                1,
+                Animation::new(pickaxe_frames),
+                item.uses,
                 false,
             ),
             crate::item::ItemType::Gear => {
-                self.hp = 1000;
+                self.hp = self.max_hp;
+                self.parts.push(item.item_type);
                 return true;
             }
             crate::item::ItemType::Shovel => {
-                Tool::Shovel(Orientation::Right, Animation::new(&shovel_frames), 1, false)
-            }
-        };
-
-        match &self.inventory {
-            Inventory::Empty => {
-                self.inventory = Inventory::Left(tool);
-            }
-            Inventory::Left(l) => {
-                self.inventory = Inventory::Both(l.clone(), tool);
-            }
-            Inventory::Right(r) => self.inventory = Inventory::Both(tool, r.clone()),
-            _ => return false,
-        };
-
-        // Guter Code :/
-        match &mut self.inventory {
-            Inventory::Left(l) => match l {
-                Tool::Axe(or, _, _, _) => *or = Orientation::Left,
-                Tool::Pickaxe(or, _, _, _) => *or = Orientation::Left,
-                Tool::Shovel(or, _, _, _) => *or = Orientation::Left,
-            },
-            Inventory::Right(l) => match l {
-                Tool::Axe(or, _, _, _) => *or = Orientation::Right,
-                Tool::Pickaxe(or, _, _, _) => *or = Orientation::Right,
-                Tool::Shovel(or, _, _, _) => *or = Orientation::Right,
-            },
-            Inventory::Both(l, r) => {
-                match l {
-                    Tool::Axe(or, _, _, _) => *or = Orientation::Left,
-                    Tool::Pickaxe(or, _, _, _) => *or = Orientation::Left,
-                    Tool::Shovel(or, _, _, _) => *or = Orientation::Left,
-                }
-
-                match r {
-                    Tool::Axe(or, _, _, _) => *or = Orientation::Right,
-                    Tool::Pickaxe(or, _, _, _) => *or = Orientation::Right,
-                    Tool::Shovel(or, _, _, _) => *or = Orientation::Right,
-                }
+                Tool::Shovel(Orientation::Right, Animation::new(shovel_frames), item.uses, false)
             }
-
-            _ => return false,
         };
 
-        true
+        self.inventory.add(tool).is_ok()
     }
 
     pub fn up(&mut self) {
@@ -621,49 +534,40 @@ impl<'a> Player<'a> {
 
     pub fn left(&mut self) {
         self.movement.left();
-        self.orientation = Orientation::Left;
+        if !self.state.locks_movement() {
+            self.orientation = Orientation::Left;
+        }
     }
 
     pub fn right(&mut self) {
         self.movement.right();
-        self.orientation = Orientation::Right;
+        if !self.state.locks_movement() {
+            self.orientation = Orientation::Right;
+        }
     }
 
     pub fn switch_tools(&mut self) {
-        self.inventory = match &self.inventory {
-            Inventory::Empty => Inventory::Empty,
-            Inventory::Left(l) => Inventory::Right(l.clone()),
-            Inventory::Right(r) => Inventory::Left(r.clone()),
-            Inventory::Both(l, r) => Inventory::Both(r.clone(), l.clone()),
-        };
+        self.inventory.cycle();
+    }
 
-        match &mut self.inventory {
-            Inventory::Left(l) => match l {
-                Tool::Axe(or, _, _, _) => *or = Orientation::Left,
-                Tool::Pickaxe(or, _, _, _) => *or = Orientation::Left,
-                Tool::Shovel(or, _, _, _) => *or = Orientation::Left,
-            },
-            Inventory::Right(l) => match l {
-                Tool::Axe(or, _, _, _) => *or = Orientation::Right,
-                Tool::Pickaxe(or, _, _, _) => *or = Orientation::Right,
-                Tool::Shovel(or, _, _, _) => *or = Orientation::Right,
-            },
-            Inventory::Both(l, r) => {
-                match l {
-                    Tool::Axe(or, _, _, _) => *or = Orientation::Left,
-                    Tool::Pickaxe(or, _, _, _) => *or = Orientation::Left,
-                    Tool::Shovel(or, _, _, _) => *or = Orientation::Left,
-                }
+    pub fn select_backpack_slot(&mut self, slot: usize) {
+        self.inventory.select_slot(slot);
+    }
 
-                match r {
-                    Tool::Axe(or, _, _, _) => *or = Orientation::Right,
-                    Tool::Pickaxe(or, _, _, _) => *or = Orientation::Right,
-                    Tool::Shovel(or, _, _, _) => *or = Orientation::Right,
-                }
-            }
+    pub fn swap_with_backpack(&mut self) {
+        self.inventory.swap_with_backpack(&self.orientation);
+    }
 
-            _ => (),
-        };
+    // Das Tool in der Hand, in die der Spieler gerade schaut. Kaputte Tools kann man nicht ablegen.
+    pub fn drop_tool(&mut self) -> Option<Tool> {
+        let usable = self
+            .inventory
+            .hand(&self.orientation)
+            .is_some_and(|tool| tool.uses_left() > 0);
+        if !usable {
+            return None;
+        }
+        self.inventory.take_hand(&self.orientation)
     }
 }
 
@@ -698,7 +602,7 @@ impl Movement {
     }
 }
 
-impl Collision for Player<'_> {
+impl Collision for Player {
     fn collision_with_rec(&self, other: &Rectangle) -> bool {
         Rectangle::new(self.pos.x, self.pos.y, self.dimensions.x, self.dimensions.y)
             .check_collision_recs(other)
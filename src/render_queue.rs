@@ -0,0 +1,31 @@
+// Alles, was vor oder hinter dem Spieler stehen kann, wird nach der Unterkante (Füße) sortiert
+// gezeichnet. Was weiter unten steht, wird später gezeichnet und verdeckt damit das dahinter.
+#[derive(Clone, Copy)]
+pub enum Renderable {
+    Tile { layer: i32, x: i32, y: i32 },
+    Item(usize),
+    Creature(usize),
+    Player,
+}
+
+pub struct RenderQueue {
+    entries: Vec<(f32, Renderable)>,
+}
+
+impl RenderQueue {
+    pub fn new() -> Self {
+        RenderQueue {
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, foot_y: f32, renderable: Renderable) {
+        self.entries.push((foot_y, renderable));
+    }
+
+    // Sortiert und leert die Queue. Bei gleicher Höhe bleibt die Reihenfolge vom push.
+    pub fn drain_sorted(&mut self) -> impl Iterator<Item = Renderable> + '_ {
+        self.entries.sort_by(|a, b| a.0.total_cmp(&b.0));
+        self.entries.drain(..).map(|(_, renderable)| renderable)
+    }
+}
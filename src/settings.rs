@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::audiomanager::AudioSettings;
+use crate::intro::DEFAULT_TEXT_SPEED;
+use crate::localization::DEFAULT_LANGUAGE;
+
+// Liegt neben dem Spiel, nicht in assets/, damit der Hot Reload nicht anspringt
+pub const SETTINGS_PATH: &str = "settings.ron";
+
+// Alles, was der Spieler einstellt und beim nächsten Start wieder so haben will
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Settings {
+    #[serde(default)]
+    pub audio: AudioSettings,
+    // Typewriter im Intro, Zeichen pro Sekunde
+    #[serde(default = "default_text_speed")]
+    pub text_speed: f32,
+    // Name der Datei in assets/lang/ ohne .ron, z.B. "de"
+    #[serde(default = "default_language")]
+    pub language: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            audio: AudioSettings::default(),
+            text_speed: DEFAULT_TEXT_SPEED,
+            language: default_language(),
+        }
+    }
+}
+
+fn default_language() -> String {
+    DEFAULT_LANGUAGE.to_string()
+}
+
+fn default_text_speed() -> f32 {
+    DEFAULT_TEXT_SPEED
+}
+
+impl Settings {
+    // Keine Datei (erster Start) oder kaputte Datei: Standardwerte
+    pub fn load(path: &str) -> Settings {
+        let Ok(s) = fs::read_to_string(path) else {
+            return Settings::default();
+        };
+        match ron::from_str(s.as_str()) {
+            Ok(settings) => settings,
+            Err(why) => {
+                println!("couldn't parse {}: {}, using defaults", path, why);
+                Settings::default()
+            }
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let s = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|why| format!("couldn't serialize settings: {}", why))?;
+        fs::write(path, s).map_err(|why| format!("couldn't write {}: {}", path, why))
+    }
+}
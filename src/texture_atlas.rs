@@ -1,27 +1,266 @@
 use raylib::prelude::*;
-use std::{collections::HashMap, error};
+use std::cell::RefCell;
+use std::collections::HashMap;
 
+use crate::animation::{AnimationClip, AnimationMode};
+use crate::asset_manifest::{AnimationConfig, AssetManifest};
+
+// Größe einer Atlas-Seite. 1024² reicht für alle Sprites im Moment locker auf eine Seite.
+const PAGE_SIZE: i32 = 1024;
+// Abstand zwischen den Sprites, damit beim Skalieren nichts vom Nachbarn reinblutet
+const PADDING: i32 = 1;
+// Schachbrett, das statt fehlender Texturen gezeichnet wird. Fällt sofort auf.
+const FALLBACK: &str = "fallback";
+
+// Zeigt auf ein Sprite in einer Atlas-Seite. Billig zu kopieren, hält nichts fest.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpriteRef {
+    pub page: usize,
+    pub source: Rectangle,
+}
+
+impl SpriteRef {
+    pub fn width(&self) -> f32 {
+        self.source.width
+    }
+
+    pub fn height(&self) -> f32 {
+        self.source.height
+    }
+}
+
+// Alle Sprites werden beim Start in wenige große Texturen (Seiten) gepackt.
+// Erst load_manifest()/load_image() für alle Pfade, dann einmal pack(), danach gibt es SpriteRefs.
 pub struct TextureAtlas {
-    textures: HashMap<String, Texture2D>,
+    pages: Vec<Texture2D>,
+    // Die Seiten nochmal im RAM, damit man beim Hot Reload einzelne Sprites austauschen kann
+    page_images: Vec<Image>,
+    sprites: HashMap<String, SpriteRef>,
+    // Sprites, die beim Hot Reload wegen neuer Größe umgezogen sind: (Seite, x, y) der alten Stelle -> neue.
+    // Alte SpriteRefs (Animationen, Tools, Tiles, ...) landen beim Zeichnen so trotzdem beim neuen Bild.
+    moved: HashMap<(usize, i32, i32), SpriteRef>,
+    animations: HashMap<String, AnimationConfig>,
+    pending: Vec<(String, Image)>,
+    // Wo auf der letzten Seite das nächste Sprite hinkommt: x, y und Höhe des aktuellen Regalbretts
+    cursor: (i32, i32, i32),
+    // Alles, was nicht geladen werden konnte oder nach dem gefragt wurde, obwohl es fehlt
+    missing: RefCell<Vec<String>>,
 }
 
 impl TextureAtlas {
     pub fn new() -> Self {
         println!("TEXTURE ATLAS CREATED");
         TextureAtlas {
-            textures: HashMap::new(),
-        }        
+            pages: Vec::new(),
+            page_images: Vec::new(),
+            sprites: HashMap::new(),
+            moved: HashMap::new(),
+            animations: HashMap::new(),
+            pending: Vec::new(),
+            // Noch keine Seite, das erste Sprite macht eine auf
+            cursor: (PAGE_SIZE, PAGE_SIZE, 0),
+            missing: RefCell::new(Vec::new()),
+        }
+    }
+
+    // Kaputte oder fehlende Dateien werden nur gemerkt, statt ihnen gibt es später das Schachbrett
+    pub fn load_image(&mut self, path: &str) {
+        if self.sprites.contains_key(path) || self.pending.iter().any(|(p, _)| p == path) {
+            return;
+        }
+        match Image::load_image(path) {
+            Ok(image) => self.pending.push((path.to_string(), image)),
+            Err(why) => {
+                println!("couldn't load {}: {}", path, why);
+                self.report_missing(path);
+                // Ein eigenes Schachbrett statt dem geteilten, damit der Hot Reload später genau dieses Sprite ersetzen kann
+                self.pending.push((path.to_string(), checkerboard()));
+            }
+        }
+    }
+
+    fn report_missing(&self, path: &str) {
+        let mut missing = self.missing.borrow_mut();
+        if !missing.iter().any(|p| p == path) {
+            missing.push(path.to_string());
+        }
+    }
+
+    pub fn missing(&self) -> Vec<String> {
+        self.missing.borrow().clone()
+    }
+
+    // Lädt alle Texturen aus dem Manifest und merkt sich die Animationen. Danach noch pack() aufrufen.
+    pub fn load_manifest(&mut self, manifest: &AssetManifest) {
+        for path in manifest.texture_paths() {
+            self.load_image(path);
+        }
+        self.animations.extend(manifest.animations.iter().map(|(name, config)| (name.clone(), config.clone())));
+    }
+
+    // Shelf-Packing: Sprites nach Höhe sortieren und zeilenweise auffüllen.
+    // Passt eine Zeile nicht mehr auf die Seite, kommt eine neue Seite. Spätere Aufrufe (Hot Reload)
+    // machen auf der letzten Seite weiter, statt jedes Mal eine neue anzufangen.
+    pub fn pack(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) -> Result<(), String> {
+        let mut pending = std::mem::take(&mut self.pending);
+        if !self.sprites.contains_key(FALLBACK) {
+            pending.push((FALLBACK.to_string(), checkerboard()));
+        }
+        pending.sort_by_key(|(_, image)| std::cmp::Reverse(image.height()));
+
+        let mut pages: Vec<Image> = Vec::new();
+        let (mut x, mut y, mut shelf_height) = self.cursor;
+        let mut last_page_changed = false;
+
+        for (path, image) in pending.iter() {
+            let (width, height) = (image.width(), image.height());
+            if width + PADDING * 2 > PAGE_SIZE || height + PADDING * 2 > PAGE_SIZE {
+                return Err(format!("{} ({}x{}) is too big for an atlas page", path, width, height));
+            }
+
+            if x + width + PADDING > PAGE_SIZE {
+                x = PADDING;
+                y += shelf_height + PADDING;
+                shelf_height = 0;
+            }
+            if y + height + PADDING > PAGE_SIZE {
+                pages.push(Image::gen_image_color(PAGE_SIZE, PAGE_SIZE, Color::BLANK));
+                x = PADDING;
+                y = PADDING;
+                shelf_height = 0;
+            }
+
+            let source = Rectangle::new(0.0, 0.0, width as f32, height as f32);
+            let dest = Rectangle::new(x as f32, y as f32, width as f32, height as f32);
+            let page = self.page_images.len() + pages.len() - 1;
+            // Ohne neue Seite ist noch Platz auf der letzten alten
+            let page_image = match pages.last_mut() {
+                Some(page_image) => page_image,
+                None => {
+                    last_page_changed = true;
+                    self.page_images.last_mut().unwrap()
+                }
+            };
+            page_image.draw(image, source, dest, Color::WHITE);
+            self.sprites.insert(path.clone(), SpriteRef { page, source: dest });
+
+            x += width + PADDING;
+            shelf_height = shelf_height.max(height);
+        }
+
+        self.cursor = (x, y, shelf_height);
+
+        if last_page_changed {
+            let last = self.page_images.len() - 1;
+            self.pages[last] = rl.load_texture_from_image(thread, &self.page_images[last])?;
+        }
+        for page in pages {
+            self.pages.push(rl.load_texture_from_image(thread, &page)?);
+            self.page_images.push(page);
+        }
+        println!("TEXTURE ATLAS PACKED: {} sprites on {} page(s)", self.sprites.len(), self.pages.len());
+        Ok(())
     }
 
-    pub fn store_texture(&mut self, path: &str, texture: Texture2D) {
-        self.textures.insert(path.to_string(), texture);
+    // Gleiche Größe: das Sprite wird an Ort und Stelle ersetzt, alle SpriteRefs zeigen dann aufs neue Bild.
+    // Andere Größe: wird hinten auf die letzte Seite gepackt, alte SpriteRefs leitet draw() dorthin um.
+    // Fehlte die Datei beim Start, wird sie jetzt nachgeladen. Alle anderen Texturen, die nicht im Atlas sind,
+    // interessieren hier nicht, neue kommen übers Manifest.
+    pub fn reload_texture(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, path: &str) -> Result<(), String> {
+        let was_missing = self.missing.borrow().iter().any(|p| p == path);
+        let sprite = self.sprites.get(path).copied();
+        if sprite.is_none() && !was_missing {
+            return Ok(());
+        }
+        let image = Image::load_image(path).map_err(|why| format!("couldn't load {}: {}", path, why))?;
+        self.missing.borrow_mut().retain(|p| p != path);
+
+        // Nur über get_sprite() angefragt: die SpriteRefs zeigen aufs geteilte Schachbrett, neue bekommen das Bild
+        let Some(sprite) = sprite else {
+            self.pending.push((path.to_string(), image));
+            self.pack(rl, thread)?;
+            println!("HOT RELOAD: {} (was missing)", path);
+            return Ok(());
+        };
+
+        if sprite.width() as i32 == image.width() && sprite.height() as i32 == image.height() {
+            let page = &mut self.page_images[sprite.page];
+            let dest = sprite.source;
+            page.draw_rectangle(dest.x as i32, dest.y as i32, dest.width as i32, dest.height as i32, Color::BLANK);
+            page.draw(&image, Rectangle::new(0.0, 0.0, dest.width, dest.height), dest, Color::WHITE);
+            self.pages[sprite.page] = rl.load_texture_from_image(thread, page)?;
+        } else {
+            self.sprites.remove(path);
+            self.pending.push((path.to_string(), image));
+            self.pack(rl, thread)?;
+            if let Some(new) = self.sprites.get(path).copied() {
+                self.moved.insert(moved_key(sprite), new);
+            }
+        }
+        println!("HOT RELOAD: {}", path);
+        Ok(())
     }
 
-    pub fn get_texture(&self, path: &str) -> &Texture2D {
-        if !self.textures.contains_key(path) {
-            println!("Texture {} not loaded yet", path)
+    pub fn get_sprite(&self, path: &str) -> SpriteRef {
+        if let Some(sprite) = self.sprites.get(path) {
+            return *sprite;
         }
-        return self.textures.get(path).unwrap();
+        println!("Texture {} not loaded, using fallback", path);
+        self.report_missing(path);
+        *self.sprites.get(FALLBACK).expect("atlas has not been packed yet")
     }
 
-}
\ No newline at end of file
+    pub fn get_animation(&self, name: &str) -> AnimationClip {
+        let Some(config) = self.animations.get(name).filter(|config| !config.frames.is_empty()) else {
+            println!("Animation {} not in the manifest, using fallback", name);
+            self.report_missing(&format!("animation {}", name));
+            return AnimationClip {
+                frames: vec![self.get_sprite(FALLBACK)],
+                frame_duration: 1.0,
+                mode: AnimationMode::Loop,
+                events: Vec::new(),
+                scale: 1.0,
+            };
+        };
+        AnimationClip {
+            frames: config.frames.iter().map(|path| self.get_sprite(path)).collect(),
+            frame_duration: config.frame_duration,
+            mode: config.mode,
+            events: config.events.clone(),
+            scale: config.scale,
+        }
+    }
+
+    // Wo das Sprite jetzt liegt, falls es beim Hot Reload umgezogen ist (auch mehrmals)
+    fn resolve(&self, mut sprite: SpriteRef) -> SpriteRef {
+        while let Some(new) = self.moved.get(&moved_key(sprite)) {
+            sprite = *new;
+        }
+        sprite
+    }
+
+    // Zeichnet ein Sprite aus dem Atlas. flip_x spiegelt horizontal (z.B. wenn der Spieler nach links schaut).
+    pub fn draw(&self, d: &mut impl RaylibDraw, sprite: SpriteRef, dest: Rectangle, flip_x: bool, tint: Color) {
+        let sprite = self.resolve(sprite);
+        let mut source = sprite.source;
+        if flip_x {
+            source.width = -source.width;
+        }
+        d.draw_texture_pro(&self.pages[sprite.page], source, dest, Vector2::zero(), 0.0, tint);
+    }
+
+    pub fn draw_scaled(&self, d: &mut impl RaylibDraw, sprite: SpriteRef, pos: Vector2, scale: f32, tint: Color) {
+        let sprite = self.resolve(sprite);
+        let dest = Rectangle::new(pos.x, pos.y, sprite.width() * scale, sprite.height() * scale);
+        self.draw(d, sprite, dest, false, tint);
+    }
+}
+
+fn checkerboard() -> Image {
+    Image::gen_image_checked(32, 32, 8, 8, Color::MAGENTA, Color::BLACK)
+}
+
+// Sprites werden nie übereinander gepackt, die linke obere Ecke reicht also als Schlüssel
+fn moved_key(sprite: SpriteRef) -> (usize, i32, i32) {
+    (sprite.page, sprite.source.x as i32, sprite.source.y as i32)
+}
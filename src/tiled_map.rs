@@ -5,15 +5,20 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::{fs::File, io::Read, path::Path};
 
-use crate::texture_atlas::TextureAtlas;
+use crate::asset_manifest::{Footstep, FootstepConfig};
+use crate::creature::CreatureConfig;
+use crate::item::ItemType;
+use crate::objective::Objective;
+use crate::texture_atlas::{SpriteRef, TextureAtlas};
 use crate::trait_collision::Collision;
 
 pub const TILE_WIDTH: i32 = 32;
 pub const TILE_HEIGHT: i32 = 32;
 pub const SCALE: f32 = 2.0;
+const DEFAULT_TILE_FRAME_DURATION: f32 = 0.3;
 
-pub struct TiledMap<'a> {
-    tiles_textures: HashMap<i32, &'a Texture2D>,
+pub struct TiledMap {
+    tiles_textures: HashMap<i32, SpriteRef>,
     map: Vec<TiledMapLayer>,
     pub layers: i32,
     pub size_x: i32,
@@ -23,8 +28,12 @@ pub struct TiledMap<'a> {
     pub tile_width: i32,
     pub tile_height: i32,
     pub scale: f32,
-    pub tiles_textures_paths: Vec<&'a str>,
+    pub tiles_textures_paths: Vec<&'static str>,
     animation_counter: f32,
+    // Sekunden pro Frame für animierte Tiles (Wasser, zerfallende Hindernisse)
+    pub frame_duration: f32,
+    // Wird hochgezählt, sobald sich an den Tiles etwas ändert (z.B. für den Pathfinding-Cache)
+    revision: u64,
 }
 
 type TextureID = i32;
@@ -43,6 +52,16 @@ pub enum Tile {
     AnimatedOnce(Vec<TextureID>, usize, Vec<Tags>),
 }
 
+impl Tile {
+    pub fn tags(&self) -> &Vec<Tags> {
+        match self {
+            Tile::Static(_, tags) => tags,
+            Tile::Animated(_, _, tags) => tags,
+            Tile::AnimatedOnce(_, _, tags) => tags,
+        }
+    }
+}
+
 pub fn new_stone() -> Tile {
     Tile::Static(7, vec![Tags::Barrier, Tags::Destroyable])
 }
@@ -62,12 +81,15 @@ pub fn new_pfutze() -> Tile {
 #[derive(Clone)]
 pub struct TiledMapLayer {
     tiles: Vec<Vec<Tile>>,
+    // Das Zeichen aus der .KB-Datei, aus dem das Tile gebaut wurde. Bleibt, auch wenn das Tile kaputt geht.
+    kinds: Vec<Vec<char>>,
 }
 
 impl TiledMapLayer {
     pub fn new(size_x: i32, size_y: i32) -> Self {
         TiledMapLayer {
             tiles: vec![vec![Tile::Static(0, Vec::new()); size_y as usize]; size_x as usize],
+            kinds: vec![vec!['0'; size_y as usize]; size_x as usize],
         }
     }
 
@@ -110,8 +132,8 @@ impl TiledMapLayer {
     }
 }
 
-impl<'a> TiledMap<'a> {
-    pub fn new(layers: i32, size_x: i32, size_y: i32, atlas: &'a TextureAtlas) -> Self {
+impl TiledMap {
+    pub fn new(layers: i32, size_x: i32, size_y: i32, atlas: &TextureAtlas) -> Self {
         println!("TILEDMAP ATLAS CREATED");
         let mut tiled_map = TiledMap {
             tiles_textures: HashMap::new(),
@@ -153,6 +175,8 @@ impl<'a> TiledMap<'a> {
                 /*25*/ "assets/pfutze.png",
             ],
             animation_counter: 0.0,
+            frame_duration: DEFAULT_TILE_FRAME_DURATION,
+            revision: 0,
         };
         tiled_map.load_textures(atlas);
         tiled_map.initialize_tiles();
@@ -161,7 +185,7 @@ impl<'a> TiledMap<'a> {
         tiled_map
     }
 
-    pub fn water(layers: i32, size_x: i32, size_y: i32, atlas: &'a TextureAtlas) -> Self {
+    pub fn water(layers: i32, size_x: i32, size_y: i32, atlas: &TextureAtlas) -> Self {
         let mut tiled_map = TiledMap {
             tiles_textures: HashMap::new(),
             map: vec![TiledMapLayer::new(size_x, size_y); layers as usize],
@@ -180,25 +204,30 @@ impl<'a> TiledMap<'a> {
                 /* 3*/ "assets/water3.png",
             ],
             animation_counter: 0.0,
+            frame_duration: DEFAULT_TILE_FRAME_DURATION,
+            revision: 0,
         };
         tiled_map.load_textures(atlas);
         tiled_map.init_water();
         tiled_map
     }
     
-    pub fn from(config: &MazeConfig, atlas: &'a TextureAtlas) -> Result<Self, String> {
+    pub fn from(config: &MazeConfig, atlas: &TextureAtlas) -> Result<Self, String> {
         let mut tiled_map = TiledMap::new(2, config.size.0, config.size.1, atlas);
+        tiled_map.frame_duration = config.tile_frame_duration;
         let mut ground_iter = config.ground.chars().filter(|c| c != &'\n' && c != &' ' && c != &'\r');
         let mut objects_iter = config.objects.chars().filter(|c| c != &'\n' && c != &' ' && c != &'\r');
 
         // ground
         for y in 0..tiled_map.size_y {
             for x in 0..tiled_map.size_x {
+                let kind = ground_iter.next();
+                tiled_map.set_kind(0, x, y, kind);
                 tiled_map.set_tile(
                     0,
                     x,
                     y,
-                    match ground_iter.next() {
+                    match kind {
                         Some('0') => Tile::Static(0, Vec::new()),
                         Some('1') => Tile::Animated(vec![13, 14, 15, 16], 0, vec![Tags::Barrier]),
                         Some('2') => Tile::Static(1, Vec::new()),
@@ -218,11 +247,13 @@ impl<'a> TiledMap<'a> {
         // objects
         for y in 0..tiled_map.size_y {
             for x in 0..tiled_map.size_x {
+                let kind = objects_iter.next();
+                tiled_map.set_kind(1, x, y, kind);
                 tiled_map.set_tile(
                     1,
                     x,
                     y,
-                    match objects_iter.next() {
+                    match kind {
                         Some('0') => Tile::Static(0, Vec::new()),
                         Some('1') => Tile::Animated(vec![13, 14, 15, 16], 0, vec![Tags::Barrier]),
                         Some('2') => Tile::Static(1, Vec::new()),
@@ -242,7 +273,7 @@ impl<'a> TiledMap<'a> {
         return Ok(tiled_map);
     }
 
-    fn load_textures(&mut self, atlas: &'a TextureAtlas) {
+    fn load_textures(&mut self, atlas: &TextureAtlas) {
         let texture_paths: Vec<_> = self
             .tiles_textures_paths
             .iter()
@@ -250,8 +281,8 @@ impl<'a> TiledMap<'a> {
             .map(|(id, &path)| (id as i32, path))
             .collect();
         for (id, path) in texture_paths {
-            let texture = atlas.get_texture(path);
-            self.add_tile_texture(id, texture);
+            let sprite = atlas.get_sprite(path);
+            self.add_tile_texture(id, sprite);
         }
     }
 
@@ -275,7 +306,7 @@ impl<'a> TiledMap<'a> {
                         }
                         _ => ()
                     }
-                    
+                    self.revision += 1;
                 },
                 Tile::Animated(_, _, _) => (),
                 Tile::AnimatedOnce(_, _, _) => (),
@@ -352,8 +383,8 @@ impl<'a> TiledMap<'a> {
         }
     }
 
-    pub fn add_tile_texture(&mut self, id: i32, texture: &'a Texture2D) {
-        self.tiles_textures.insert(id, texture);
+    pub fn add_tile_texture(&mut self, id: i32, sprite: SpriteRef) {
+        self.tiles_textures.insert(id, sprite);
     }
 
     pub fn set_tile(&mut self, layer: i32, x: i32, y: i32, tile: Tile) {
@@ -361,20 +392,98 @@ impl<'a> TiledMap<'a> {
             return;
         }
         self.map[layer as usize].tiles[x as usize][y as usize] = tile;
+        self.revision += 1;
+    }
+
+    fn set_kind(&mut self, layer: i32, x: i32, y: i32, kind: Option<char>) {
+        if let Some(kind) = kind.filter(|_| x < self.size_x && y < self.size_y) {
+            self.map[layer as usize].kinds[x as usize][y as usize] = kind;
+        }
+    }
+
+    // '1' im ground der Maze-Datei
+    fn is_water(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && x < self.size_x && y < self.size_y && self.map[0].kinds[x as usize][y as usize] == '1'
+    }
+
+    // Welche Schritte man auf dem Tile hört. Objekte (Pfütze, Reste von kaputten Hindernissen) gehen vor,
+    // dann das Ufer, dann der Boden.
+    pub fn footstep_at<'a>(&self, tile: (i32, i32), footsteps: &'a FootstepConfig) -> Option<&'a Footstep> {
+        let (x, y) = tile;
+        if x < 0 || y < 0 || x >= self.size_x || y >= self.size_y {
+            return None;
+        }
+        let declared = |layer: &TiledMapLayer| footsteps.tiles.get(&layer.kinds[x as usize][y as usize]);
+
+        if let Some(footstep) = self.map.iter().skip(1).rev().find_map(declared) {
+            return Some(footstep);
+        }
+        let near_water = (-1..=1).any(|dx| (-1..=1).any(|dy| self.is_water(x + dx, y + dy)));
+        if let Some(shore) = footsteps.shore.as_ref().filter(|_| near_water) {
+            return Some(shore);
+        }
+        self.map.first().and_then(declared)
+    }
+
+    // Wasser-Tiles, die an Land grenzen
+    pub fn shoreline(&self) -> Vec<(i32, i32)> {
+        let mut shore = Vec::new();
+        for x in 0..self.size_x {
+            for y in 0..self.size_y {
+                if !self.is_water(x, y) {
+                    continue;
+                }
+                let touches_land = [(1, 0), (-1, 0), (0, 1), (0, -1)].iter().any(|(dx, dy)| {
+                    let (nx, ny) = (x + dx, y + dy);
+                    nx >= 0 && ny >= 0 && nx < self.size_x && ny < self.size_y && !self.is_water(nx, ny)
+                });
+                if touches_land {
+                    shore.push((x, y));
+                }
+            }
+        }
+        shore
+    }
+
+    pub fn find_tiles_with_tag(&self, tag: Tags) -> Vec<(i32, i32)> {
+        let mut found = Vec::new();
+        for x in 0..self.size_x {
+            for y in 0..self.size_y {
+                if self.tiles_at(x, y).any(|tile| tile.tags().contains(&tag)) {
+                    found.push((x, y));
+                }
+            }
+        }
+        found
     }
 
-    pub fn get_tile_texture(&self, layer: i32, x: i32, y: i32) -> Option<&Texture2D> {
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn tiles_at(&self, x: i32, y: i32) -> impl Iterator<Item = &Tile> {
+        self.map
+            .iter()
+            .filter(move |_| x >= 0 && y >= 0 && x < self.size_x && y < self.size_y)
+            .map(move |layer| &layer.tiles[x as usize][y as usize])
+    }
+
+    pub fn get_tile_texture(&self, layer: i32, x: i32, y: i32) -> Option<SpriteRef> {
         if x >= self.size_x || y >= self.size_y {
             return None;
         }
-        let tile = &self.map[layer as usize].tiles[x as usize][y as usize];
+        self.sprite_of(&self.map[layer as usize].tiles[x as usize][y as usize])
+    }
+
+    // Das Sprite, mit dem ein Tile gerade gezeichnet wird
+    pub fn sprite_of(&self, tile: &Tile) -> Option<SpriteRef> {
         self.tiles_textures
             .get(&match tile {
                 Tile::Static(id, _) => *id,
                 Tile::Animated(items, current, _) => items[*current] as i32,
                 Tile::AnimatedOnce(items, current, _) => items[*current] as i32,
             })
-            .map(|v| *v)
+            .copied()
     }
 
     pub fn get_tile_id(&self, layer: i32, x: i32, y: i32) -> Option<i32> {
@@ -391,12 +500,15 @@ impl<'a> TiledMap<'a> {
 
     pub fn update_animated_tiles(&mut self, delta_time: f32) {
         self.animation_counter += delta_time;
-        if self.animation_counter < 0.3 {
-            return;
+        let frame_duration = self.frame_duration.max(0.001);
+        while self.animation_counter >= frame_duration {
+            self.animation_counter -= frame_duration;
+            self.step_animated_tiles();
         }
+    }
 
-        self.animation_counter = 0.0;
-
+    fn step_animated_tiles(&mut self) {
+        let mut changed = false;
         for (z, l) in &mut self.map.iter_mut().enumerate() {
             for (x, row) in l.tiles.iter_mut().enumerate() {
                 for (y, tile) in row.iter_mut().enumerate() {
@@ -415,30 +527,109 @@ impl<'a> TiledMap<'a> {
                             *current += 1
                         }
                         Tile::AnimatedOnce(_, _, tags) => {
+                            let before = tags.len();
                             tags.retain(|tag| *tag != Tags::Barrier);
+                            changed |= tags.len() != before;
                         }
                     };
                 }
             }
         }
+        if changed {
+            self.revision += 1;
+        }
     }
 
-    pub fn render(&self, d: &mut RaylibMode2D<'_, RaylibDrawHandle>) {
+    pub fn render(&self, d: &mut RaylibMode2D<'_, RaylibDrawHandle>, atlas: &TextureAtlas) {
         for layer in 0..self.layers {
-            for x in 0..self.size_x {
-                for y in 0..self.size_y {
-                    if let Some(texture) = self.get_tile_texture(layer, x, y) {
-                        let position = Vector2 {
-                            x: (x * self.tile_width) as f32 * self.scale + self.shift_x as f32,
-                            y: (y * self.tile_height) as f32 * self.scale + self.shift_y as f32,
-                        };
-                        d.draw_texture_ex(texture, position, 0.0, self.scale, Color::WHITE);
-                    }
-                }
+            self.render_layer(d, atlas, layer);
+        }
+    }
+
+    pub fn render_layer(&self, d: &mut RaylibMode2D<'_, RaylibDrawHandle>, atlas: &TextureAtlas, layer: i32) {
+        for x in 0..self.size_x {
+            for y in 0..self.size_y {
+                self.render_tile(d, atlas, layer, x, y);
             }
         }
     }
 
+    pub fn render_tile(
+        &self,
+        d: &mut RaylibMode2D<'_, RaylibDrawHandle>,
+        atlas: &TextureAtlas,
+        layer: i32,
+        x: i32,
+        y: i32,
+    ) {
+        if let Some(sprite) = self.get_tile_texture(layer, x, y) {
+            let position = Vector2 {
+                x: (x * self.tile_width) as f32 * self.scale + self.shift_x as f32,
+                y: (y * self.tile_height) as f32 * self.scale + self.shift_y as f32,
+            };
+            atlas.draw_scaled(d, sprite, position, self.scale, Color::WHITE);
+        }
+    }
+
+    // Die ganze Map in Weltkoordinaten
+    pub fn bounds(&self) -> Rectangle {
+        Rectangle::new(
+            self.shift_x as f32,
+            self.shift_y as f32,
+            (self.size_x * self.tile_width) as f32 * self.scale,
+            (self.size_y * self.tile_height) as f32 * self.scale,
+        )
+    }
+
+    // Unterkante eines Tiles, danach wird beim Zeichnen sortiert
+    pub fn tile_foot_y(&self, y: i32) -> f32 {
+        ((y + 1) * self.tile_height) as f32 * self.scale + self.shift_y as f32
+    }
+
+    // Gemeinsame Kollision für alles, was sich auf der Map bewegt (Spieler, Krabben, ...)
+    pub fn is_blocked(&self, rect: &Rectangle) -> bool {
+        (0..self.layers).any(|layer| {
+            self.get_collision_tiles_with_layer(layer, rect)
+                .is_some_and(|tiles| tiles.iter().any(|(tile, _)| tile.tags().contains(&Tags::Barrier)))
+        })
+    }
+
+    // Erst ganz bewegen, sonst nur in y, sonst nur in x, sonst stehen bleiben
+    pub fn slide(&self, old_pos: Vector2, delta: Vector2, hitbox: impl Fn(Vector2) -> Rectangle) -> Vector2 {
+        let candidates = [
+            old_pos + delta,
+            Vector2::new(old_pos.x, old_pos.y + delta.y),
+            Vector2::new(old_pos.x + delta.x, old_pos.y),
+        ];
+        for candidate in candidates {
+            if !self.is_blocked(&hitbox(candidate)) {
+                return candidate;
+            }
+        }
+        old_pos
+    }
+
+    pub fn tile_at(&self, pos: Vector2) -> (i32, i32) {
+        (
+            ((pos.x - self.shift_x as f32) / (self.tile_width as f32 * self.scale)).floor() as i32,
+            ((pos.y - self.shift_y as f32) / (self.tile_height as f32 * self.scale)).floor() as i32,
+        )
+    }
+
+    pub fn tile_center(&self, tile: (i32, i32)) -> Vector2 {
+        Vector2::new(
+            (tile.0 as f32 + 0.5) * self.tile_width as f32 * self.scale + self.shift_x as f32,
+            (tile.1 as f32 + 0.5) * self.tile_height as f32 * self.scale + self.shift_y as f32,
+        )
+    }
+
+    pub fn is_tile_blocked(&self, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || x >= self.size_x || y >= self.size_y {
+            return true;
+        }
+        self.tiles_at(x, y).any(|tile| tile.tags().contains(&Tags::Barrier))
+    }
+
     pub fn get_collision_tiles_with_layer(
         &self,
         layer: i32,
@@ -464,11 +655,56 @@ pub struct MazeConfig {
     pub shovels: Vec<(i32, i32)>,
     pub gears: Vec<(i32, i32)>,
 
+    // Tools mit eigener Haltbarkeit, z.B. für Rätsel, bei denen man ein Tool woanders hinbringen muss
+    #[serde(default)]
+    pub items: Vec<PlacedItem>,
+
+    #[serde(default)]
+    pub creatures: Vec<CreatureConfig>,
+
+    #[serde(default)]
+    pub objective: Objective,
+
+    // Max HP des Spielers in diesem Level, die HP-Stufen sind relativ dazu
+    #[serde(default = "default_max_hp")]
+    pub max_hp: i32,
+
+    // Wie schnell Wasser & Co. animiert sind, Sekunden pro Frame
+    #[serde(default = "default_tile_frame_duration")]
+    pub tile_frame_duration: f32,
+
     pub ground: String,
     pub objects: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlacedItem {
+    pub item: ItemType,
+    pub pos: (i32, i32),
+    #[serde(default = "default_uses")]
+    pub uses: i32,
+}
+
+fn default_uses() -> i32 {
+    1
+}
+
+fn default_max_hp() -> i32 {
+    1000
+}
+
+fn default_tile_frame_duration() -> f32 {
+    DEFAULT_TILE_FRAME_DURATION
+}
+
 impl MazeConfig {
+    pub fn player_spawn(&self) -> Vector2 {
+        Vector2::new(
+            ((self.player.0 * TILE_WIDTH) as f32 + TILE_WIDTH as f32 / 2.0) * SCALE,
+            ((self.player.1 * TILE_HEIGHT) as f32 + TILE_HEIGHT as f32 / 2.0) * SCALE,
+        )
+    }
+
     pub fn new(path: &str) -> Result<MazeConfig, String> {
         // Create a path to the desired file
         let path = Path::new(path);
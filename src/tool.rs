@@ -1,92 +1,132 @@
 use raylib::prelude::*;
 
-use crate::player::{self, Animation, Orientation};
+use crate::item::ItemType;
+use crate::animation::Animation;
+use crate::player::{self, Orientation};
+use crate::texture_atlas::{SpriteRef, TextureAtlas};
 
 type UsesLeft = i32;
 type AnimationRunning = bool;
 
 #[derive(Clone)]
-pub enum Tool<'a> {
-    Axe(Orientation, Animation<'a>, UsesLeft, AnimationRunning),
-    Pickaxe(Orientation, Animation<'a>, UsesLeft, AnimationRunning),
-    Shovel(Orientation, Animation<'a>, UsesLeft, AnimationRunning),
+pub enum Tool {
+    Axe(Orientation, Animation, UsesLeft, AnimationRunning),
+    Pickaxe(Orientation, Animation, UsesLeft, AnimationRunning),
+    Shovel(Orientation, Animation, UsesLeft, AnimationRunning),
 }
 
 const SCALE: f32 = 1.0;
 
-impl<'a> Tool<'a> {
-    pub fn use_tool(&mut self) {
+impl Tool {
+    pub fn item_type(&self) -> ItemType {
         match self {
-            Tool::Axe(orientation, _, _, animation_running) => {
-                match orientation {
-                    Orientation::Left => *animation_running = true,
-                    Orientation::Right => *animation_running = true
-                }
-            }
-            Tool::Pickaxe(orientation, _, _, animation_running) => {
-                match orientation {
-                    Orientation::Left => *animation_running = true,
-                    Orientation::Right => *animation_running = true
-                }
-            }
-            Tool::Shovel(orientation, _, _, animation_running) => {
-                match orientation {
-                    Orientation::Left => *animation_running = true,
-                    Orientation::Right => *animation_running = true
-                }
-            }
+            Tool::Axe(_, _, _, _) => ItemType::Axe,
+            Tool::Pickaxe(_, _, _, _) => ItemType::Pickaxe,
+            Tool::Shovel(_, _, _, _) => ItemType::Shovel,
         }
     }
 
-    pub fn update(&mut self) {
+    pub fn uses_left(&self) -> i32 {
         match self {
-            Tool::Axe(_, animation, _, animation_running) => {
-                if *animation_running {
-                    animation.update();
-                }
-                if animation.index == 0 {
-                    *animation_running = false;
-                    
-                }
-            }
-            Tool::Pickaxe(_, animation, _, animation_running) => {
-                if *animation_running {
-                    animation.update();
-                }
-                if animation.index == 0 {
-                    *animation_running = false;
-                }
-            }
-            Tool::Shovel(_, animation, _, animation_running) => {
-                if *animation_running {
-                    animation.update();
-                }
-                if animation.index == 0 {
-                    *animation_running = false;
-                }
-            }
+            Tool::Axe(_, _, u, _) => *u,
+            Tool::Pickaxe(_, _, u, _) => *u,
+            Tool::Shovel(_, _, u, _) => *u,
+        }
+    }
+
+    // Kaputt ist ein Tool erst, wenn auch die Animation fertig ist
+    pub fn is_broken(&self) -> bool {
+        match self {
+            Tool::Axe(_, _, u, b) => *u == 0 && !b,
+            Tool::Pickaxe(_, _, u, b) => *u == 0 && !b,
+            Tool::Shovel(_, _, u, b) => *u == 0 && !b,
+        }
+    }
+
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        match self {
+            Tool::Axe(or, _, _, _) => *or = orientation,
+            Tool::Pickaxe(or, _, _, _) => *or = orientation,
+            Tool::Shovel(or, _, _, _) => *or = orientation,
         }
     }
 
-    pub fn render(&mut self, d: &mut RaylibMode2D<'_, RaylibDrawHandle>, player_pos: Vector2, elapsed_time: f32, delta_time: f32) {
+    pub fn icon(&self) -> SpriteRef {
+        match self {
+            Tool::Axe(_, animation, _, _) => animation.current(),
+            Tool::Pickaxe(_, animation, _, _) => animation.current(),
+            Tool::Shovel(_, animation, _, _) => animation.current(),
+        }
+    }
+
+    // Ausholen. Während die Animation noch läuft, passiert nichts.
+    pub fn use_tool(&mut self) {
+        let (animation, animation_running) = match self {
+            Tool::Axe(_, animation, _, running) => (animation, running),
+            Tool::Pickaxe(_, animation, _, running) => (animation, running),
+            Tool::Shovel(_, animation, _, running) => (animation, running),
+        };
+        if !*animation_running {
+            animation.restart();
+            *animation_running = true;
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        match self {
+            Tool::Axe(_, _, _, running) => *running,
+            Tool::Pickaxe(_, _, _, running) => *running,
+            Tool::Shovel(_, _, _, running) => *running,
+        }
+    }
+
+    // Schwung abbrechen, bevor er trifft
+    pub fn cancel(&mut self) {
+        let (animation, animation_running) = match self {
+            Tool::Axe(_, animation, _, running) => (animation, running),
+            Tool::Pickaxe(_, animation, _, running) => (animation, running),
+            Tool::Shovel(_, animation, _, running) => (animation, running),
+        };
+        animation.restart();
+        *animation_running = false;
+    }
+
+    // Gibt true zurück, wenn die Animation gerade beim "hit"-Frame angekommen ist
+    pub fn update(&mut self, delta_time: f32) -> bool {
+        let (animation, animation_running) = match self {
+            Tool::Axe(_, animation, _, running) => (animation, running),
+            Tool::Pickaxe(_, animation, _, running) => (animation, running),
+            Tool::Shovel(_, animation, _, running) => (animation, running),
+        };
+        if !*animation_running {
+            return false;
+        }
+        let events = animation.update(delta_time);
+        if animation.is_finished() {
+            *animation_running = false;
+        }
+        events.iter().any(|event| event == "hit")
+    }
+
+    pub fn render(&mut self, d: &mut RaylibMode2D<'_, RaylibDrawHandle>, atlas: &TextureAtlas, player_pos: Vector2, elapsed_time: f32, delta_time: f32) {
         
-        let mut tmp: (i32, &Texture2D) = match self {
+        let mut tmp: (i32, SpriteRef) = match self {
             Tool::Axe(orientation, animation, _, _) => {
                 match orientation {
-                    Orientation::Left => (-1, animation.current),
-                    Orientation::Right => (1, animation.current)
+                    Orientation::Left => (-1, animation.current()),
+                    Orientation::Right => (1, animation.current())
                 }
             }
             Tool::Pickaxe(orientation, animation, _, _) => {
                 match orientation {
-                    Orientation::Left => (-1, animation.current),
-                    Orientation::Right => (1, animation.current)
+                    Orientation::Left => (-1, animation.current()),
+                    Orientation::Right => (1, animation.current())
                 }
             }
             Tool::Shovel(orientation, animation, _, _) => {
                 match orientation {
-                    Orientation::Left => (-1, animation.current),
-                    Orientation::Right => (1, animation.current)
+                    Orientation::Left => (-1, animation.current()),
+                    Orientation::Right => (1, animation.current())
                 }
             }
         };
@@ -100,22 +140,16 @@ impl<'a> Tool<'a> {
         }
 
 
-        d.draw_texture_pro(
+        atlas.draw(
+            d,
             tmp.1,
-            Rectangle::new(
-                0.0,
-                0.0,
-                (tmp.0 * tmp.1.width()) as f32,
-                tmp.1.height() as f32,
-            ),
             Rectangle::new(
                 x,
                 player_pos.y + 12.0 + ((elapsed_time*4.0).sin() * 4.0) + 7.0,
-                (tmp.1.width() as f32 * SCALE) as f32,
-                (tmp.1.height() as f32 * SCALE) as f32,
+                tmp.1.width() * SCALE,
+                tmp.1.height() * SCALE,
             ),
-            Vector2::zero(),
-            0 as f32,
+            tmp.0 == -1,
             Color::WHITE,
         );
 